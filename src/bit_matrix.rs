@@ -0,0 +1,309 @@
+use std::fmt::{Formatter, Display};
+
+use crate::matrix_base::*;
+use crate::modp::ModP;
+use crate::error::*;
+
+/// The two-element field, where `+` is XOR and `*` is AND.
+pub type GF2 = ModP<2>;
+
+static GF2_ZERO : GF2 = ModP::new(0);
+static GF2_ONE : GF2 = ModP::new(1);
+
+const WORD_BITS : usize = 64;
+
+/// Gauss-Jordan elimination over GF(2) on packed rows, eliminating the first
+/// `cols` columns. Each row update is a XOR of whole words, starting at the
+/// word of the pivot column. Returns the pivot columns.
+fn eliminate(data : &mut [u64], rows : usize, words : usize, cols : usize) -> Vec<usize> {
+    let mut pivots = Vec::new();
+    for c in 0..cols {
+        let r = pivots.len();
+        if r == rows {
+            break;
+        }
+        let (w, mask) = (c / WORD_BITS, 1u64 << (c % WORD_BITS));
+        let p = match (r..rows).find(|&i| data[i * words + w] & mask != 0) {
+            Some(p) => p,
+            None => continue,
+        };
+        if p != r {
+            for k in w..words {
+                data.swap(r * words + k, p * words + k);
+            }
+        }
+        let (head, tail) = data.split_at_mut(r * words);
+        let (pivot, tail) = tail.split_at_mut(words);
+        for row in head.chunks_mut(words).chain(tail.chunks_mut(words)) {
+            if row[w] & mask != 0 {
+                for k in w..words {
+                    row[k] ^= pivot[k];
+                }
+            }
+        }
+        pivots.push(c);
+    }
+    pivots
+}
+
+/// A dense matrix over GF(2) with every row packed into `u64` words, one bit
+/// per element, so row additions are word-wise XOR.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitMatrix {
+    row : usize,
+    col : usize,
+    words : usize,
+    data : Vec<u64>,
+}
+
+impl BitMatrix {
+    pub fn get_bit(self : &Self, row : usize, col : usize) -> bool {
+        self.data[row * self.words + col / WORD_BITS] & (1u64 << (col % WORD_BITS)) != 0
+    }
+
+    pub fn set_bit(self : &mut Self, row : usize, col : usize, value : bool) {
+        let mask = 1u64 << (col % WORD_BITS);
+        let w = &mut self.data[row * self.words + col / WORD_BITS];
+        if value {
+            *w |= mask;
+        } else {
+            *w &= !mask;
+        }
+    }
+
+    /// The packed words of one row; bits past the last column are zero.
+    pub fn get_row_words(self : &Self, row : usize) -> &[u64] {
+        &self.data[row * self.words..(row + 1) * self.words]
+    }
+
+    /// Reduced row echelon form and pivot columns, like `Matrix::rref`.
+    pub fn rref(self : &Self) -> (BitMatrix, Vec<usize>) {
+        let mut m = self.clone();
+        let pivots = eliminate(&mut m.data, m.row, m.words, m.col);
+        (m, pivots)
+    }
+
+    pub fn rank(self : &Self) -> usize {
+        self.rref().1.len()
+    }
+
+    /// A basis of the kernel `{ x : A * x = 0 }` as the columns of an
+    /// `n x (n - rank)` matrix.
+    pub fn kernel(self : &Self) -> BitMatrix {
+        let (r, pivots) = self.rref();
+        let mut is_pivot = vec![false; self.col];
+        for &p in pivots.iter() {
+            is_pivot[p] = true;
+        }
+        let free : Vec<usize> = (0..self.col).filter(|&c| !is_pivot[c]).collect();
+        let mut basis = BitMatrix::new(self.col, free.len());
+        for (k, &f) in free.iter().enumerate() {
+            basis.set_bit(f, k, true);
+            for (i, &p) in pivots.iter().enumerate() {
+                if r.get_bit(i, f) {
+                    basis.set_bit(p, k, true);
+                }
+            }
+        }
+        basis
+    }
+
+    /// One solution of `A * x = b`, with the free variables set to zero, or
+    /// `None` when the system is inconsistent.
+    pub fn solve(self : &Self, b : &[bool]) -> Option<Vec<bool>> {
+        match self.try_solve(b) {
+            Ok(x) => Some(x),
+            Err(MatrixError::Singular) => None,
+            Err(e) => panic!("bit matrix solve error : {}", e),
+        }
+    }
+
+    pub fn try_solve(self : &Self, b : &[bool]) -> Result<Vec<bool>, MatrixError> {
+        if b.len() != self.row {
+            return Err(MatrixError::DimensionMismatch { left : (self.row, self.col), right : (b.len(), 1) });
+        }
+        // eliminate on [A | b]
+        let mut augmented = BitMatrix::new(self.row, self.col + 1);
+        for i in 0..self.row {
+            augmented.data[i * augmented.words..i * augmented.words + self.words].copy_from_slice(self.get_row_words(i));
+            augmented.set_bit(i, self.col, b[i]);
+        }
+        let pivots = eliminate(&mut augmented.data, augmented.row, augmented.words, self.col);
+        if (pivots.len()..self.row).any(|i| augmented.get_bit(i, self.col)) {
+            return Err(MatrixError::Singular);
+        }
+        let mut x = vec![false; self.col];
+        for (i, &p) in pivots.iter().enumerate() {
+            x[p] = augmented.get_bit(i, self.col);
+        }
+        Ok(x)
+    }
+}
+
+impl MatrixInit<GF2> for BitMatrix {
+    fn new(row : usize, col : usize) -> BitMatrix {
+        let words = col.div_ceil(WORD_BITS);
+        BitMatrix {
+            row : row,
+            col : col,
+            words : words,
+            data : vec![0; row * words],
+        }
+    }
+}
+
+impl ConstMatrix<GF2> for BitMatrix {
+    fn get_row(self : &Self) -> usize {
+        self.row
+    }
+
+    fn get_column(self : &Self) -> usize {
+        self.col
+    }
+
+    fn get(self : &Self, row : usize, col : usize) -> Option<&GF2> {
+        Some(if self.get_bit(row, col) { &GF2_ONE } else { &GF2_ZERO })
+    }
+
+    fn get_sub_matrix(&self, row_begin : usize, row : usize, col_begin : usize, col : usize) -> Self {
+        let mut m = BitMatrix::new(row, col);
+        for i in 0..row {
+            for j in 0..col {
+                if self.get_bit(row_begin + i, col_begin + j) {
+                    m.set_bit(i, j, true);
+                }
+            }
+        }
+        m
+    }
+}
+
+impl Matrix<GF2> for BitMatrix {
+    fn set(self : &mut Self, row : usize, col : usize, value : GF2) {
+        self.set_bit(row, col, value.get_value() == 1);
+    }
+
+    fn add(self : &mut Self, row : usize, col : usize, value : GF2) {
+        if value.get_value() == 1 {
+            self.data[row * self.words + col / WORD_BITS] ^= 1u64 << (col % WORD_BITS);
+        }
+    }
+
+    fn element_row_transform_swap(&mut self, row_i : usize, row_j : usize) {
+        if row_i != row_j {
+            for k in 0..self.words {
+                self.data.swap(row_i * self.words + k, row_j * self.words + k);
+            }
+        }
+    }
+
+    fn element_row_transform_multi(&mut self, row : usize, k : GF2) {
+        if k.get_value() == 0 {
+            for w in self.data[row * self.words..(row + 1) * self.words].iter_mut() {
+                *w = 0;
+            }
+        }
+    }
+
+    fn element_row_transform_plus(&mut self, row_i : usize, row_j : usize, k : GF2) {
+        if k.get_value() == 0 {
+            return;
+        }
+        if row_i == row_j {
+            // a row plus itself is zero over GF(2)
+            self.element_row_transform_multi(row_i, GF2_ZERO);
+            return;
+        }
+        for w in 0..self.words {
+            let v = self.data[row_j * self.words + w];
+            self.data[row_i * self.words + w] ^= v;
+        }
+    }
+}
+
+impl Display for BitMatrix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bitmatrix[{}, {}]:\n", self.row, self.col).unwrap();
+        for row in 0..self.row {
+            for col in 0..self.col {
+                write!(f, "{}", if self.get_bit(row, col) { 1 } else { 0 }).unwrap();
+            }
+            write!(f, "\n").unwrap();
+        }
+        write!(f, "")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dense_matrix::DenseMatrix;
+
+    fn from_rows(rows : &[&str]) -> BitMatrix {
+        let mut m = BitMatrix::new(rows.len(), rows[0].len());
+        for (i, r) in rows.iter().enumerate() {
+            for (j, c) in r.chars().enumerate() {
+                m.set_bit(i, j, c == '1');
+            }
+        }
+        m
+    }
+
+    fn mul_bits(m : &BitMatrix, x : &[bool]) -> Vec<bool> {
+        (0..m.get_row()).map(|i| (0..m.get_column()).filter(|&j| m.get_bit(i, j) && x[j]).count() % 2 == 1).collect()
+    }
+
+    #[test]
+    fn bit_matrix_matches_generic() {
+        // the parity check matrix of the [7, 4] Hamming code
+        let h = from_rows(&["1010101", "0110011", "0001111"]);
+        assert_eq!(h.rank(), 3);
+        let mut dense = DenseMatrix::<GF2>::new(3, 7);
+        for i in 0..3 {
+            for j in 0..7 {
+                dense.set(i, j, *h.get(i, j).unwrap());
+            }
+        }
+        assert_eq!(Matrix::rank(&dense), 3);
+        let (r, pivots) = h.rref();
+        let (dense_r, dense_pivots) = dense.rref();
+        assert_eq!(pivots, dense_pivots);
+        for i in 0..3 {
+            for j in 0..7 {
+                assert_eq!(r.get(i, j), dense_r.get(i, j));
+            }
+        }
+        let kernel = h.kernel();
+        assert_eq!((kernel.get_row(), kernel.get_column()), (7, 4));
+        for k in 0..4 {
+            let codeword : Vec<bool> = (0..7).map(|i| kernel.get_bit(i, k)).collect();
+            assert!(mul_bits(&h, &codeword).iter().all(|&b| !b));
+        }
+        // the generic inverse also runs on the packed storage
+        let m = from_rows(&["110", "011", "001"]);
+        let inv = m.inverse().unwrap();
+        assert_eq!(inv, from_rows(&["111", "011", "001"]));
+    }
+
+    #[test]
+    fn bit_matrix_solve_across_words() {
+        let n = 130;
+        let mut m = BitMatrix::new(n, n);
+        for i in 0..n {
+            m.set_bit(i, i, true);
+            m.set_bit(i, (i * 7 + 3) % n, true);
+            m.set_bit(i, (i * 13 + 5) % n, true);
+        }
+        let x : Vec<bool> = (0..n).map(|i| i % 3 == 0).collect();
+        let b = mul_bits(&m, &x);
+        let y = m.solve(&b).unwrap();
+        assert_eq!(mul_bits(&m, &y), b);
+        let kernel = m.kernel();
+        assert_eq!(kernel.get_column(), n - m.rank());
+        assert!(m.get_row_words(0)[2] >> 2 == 0);
+        let singular = from_rows(&["11", "11"]);
+        assert_eq!(singular.solve(&[true, false]), None);
+        assert_eq!(singular.solve(&[true, true]), Some(vec![true, false]));
+        assert_eq!(singular.try_solve(&[true]).unwrap_err(), MatrixError::DimensionMismatch { left : (2, 2), right : (1, 1) });
+    }
+}
@@ -0,0 +1,436 @@
+use crate::matrix_base::*;
+use crate::dense_matrix::DenseMatrix;
+use crate::vector::Vector;
+use crate::error::*;
+
+fn check_rhs(n : usize, b : &Vector<f64>) -> Result<(), MatrixError> {
+    if b.length() != n {
+        return Err(MatrixError::DimensionMismatch { left : (n, n), right : (b.length(), 1) });
+    }
+    Ok(())
+}
+
+/// Solves `L * y = b` in place, `L` lower triangular with the given
+/// diagonal handling.
+fn forward_substitute(n : usize, l : &[f64], unit : bool, x : &mut [f64]) {
+    for i in 0..n {
+        let mut v = x[i];
+        for j in 0..i {
+            v -= l[i * n + j] * x[j];
+        }
+        x[i] = if unit { v } else { v / l[i * n + i] };
+    }
+}
+
+/// Solves `Lᵀ * y = b` in place.
+fn backward_substitute(n : usize, l : &[f64], unit : bool, x : &mut [f64]) {
+    for i in (0..n).rev() {
+        let mut v = x[i];
+        for j in (i + 1)..n {
+            v -= l[j * n + i] * x[j];
+        }
+        x[i] = if unit { v } else { v / l[i * n + i] };
+    }
+}
+
+fn lower_to_dense(n : usize, l : &[f64], unit : bool) -> DenseMatrix<f64> {
+    let mut m = DenseMatrix::new_with(n, n, 0.0);
+    for i in 0..n {
+        for j in 0..i {
+            m.set(i, j, l[i * n + j]);
+        }
+        m.set(i, i, if unit { 1.0 } else { l[i * n + i] });
+    }
+    m
+}
+
+/// Cholesky factorization `A = L * Lᵀ` of a symmetric positive definite
+/// matrix. Only the lower triangle of `A` is read.
+#[derive(Clone, Debug)]
+pub struct Cholesky {
+    n : usize,
+    l : Vec<f64>,
+}
+
+impl Cholesky {
+    pub fn new(m : &DenseMatrix<f64>) -> Cholesky {
+        Cholesky::try_new(m).unwrap_or_else(|e| panic!("cholesky error : {}", e))
+    }
+
+    /// Fails with `NotPositiveDefinite { pivot }` at the first pivot that is
+    /// not strictly positive, i.e. the first leading minor that is not
+    /// positive definite.
+    pub fn try_new(m : &DenseMatrix<f64>) -> Result<Cholesky, MatrixError> {
+        check_square((m.get_row(), m.get_column()))?;
+        let n = m.get_row();
+        let a = m.as_slice();
+        let mut l = vec![0.0; n * n];
+        for j in 0..n {
+            let mut d = a[j * n + j];
+            for k in 0..j {
+                d -= l[j * n + k] * l[j * n + k];
+            }
+            if d <= 0.0 || d.is_nan() {
+                return Err(MatrixError::NotPositiveDefinite { pivot : j });
+            }
+            let d = d.sqrt();
+            l[j * n + j] = d;
+            for i in (j + 1)..n {
+                let mut v = a[i * n + j];
+                for k in 0..j {
+                    v -= l[i * n + k] * l[j * n + k];
+                }
+                l[i * n + j] = v / d;
+            }
+        }
+        Ok(Cholesky {
+            n : n,
+            l : l,
+        })
+    }
+
+    pub fn get_l(self : &Self) -> DenseMatrix<f64> {
+        lower_to_dense(self.n, &self.l, false)
+    }
+
+    pub fn solve(self : &Self, b : &Vector<f64>) -> Vector<f64> {
+        self.try_solve(b).unwrap_or_else(|e| panic!("cholesky solve error : {}", e))
+    }
+
+    pub fn try_solve(self : &Self, b : &Vector<f64>) -> Result<Vector<f64>, MatrixError> {
+        check_rhs(self.n, b)?;
+        let mut x = b.as_slice().to_vec();
+        forward_substitute(self.n, &self.l, false, &mut x);
+        backward_substitute(self.n, &self.l, false, &mut x);
+        Ok(Vector::from_vec(x))
+    }
+
+    pub fn determinant(self : &Self) -> f64 {
+        let p : f64 = (0..self.n).map(|i| self.l[i * self.n + i]).product();
+        p * p
+    }
+
+    /// `ln det(A)`, summed from the pivots so it does not overflow when
+    /// `determinant` would.
+    pub fn log_determinant(self : &Self) -> f64 {
+        2.0 * (0..self.n).map(|i| self.l[i * self.n + i].ln()).sum::<f64>()
+    }
+}
+
+/// The Bunch-Kaufman constant `(1 + √17) / 8`, which bounds the growth of
+/// the entries of `L` equally for 1x1 and 2x2 pivots.
+const BUNCH_KAUFMAN_ALPHA : f64 = 0.640_388_203_202_208;
+
+/// `P * A * Pᵀ = L * D * Lᵀ` with unit lower triangular `L`, a symmetric
+/// permutation `P` and block diagonal `D` of 1x1 and 2x2 blocks, for
+/// symmetric matrices that may be indefinite. Pivots are chosen by the
+/// Bunch-Kaufman rule, so a small or zero leading entry is swapped away or
+/// paired into a 2x2 block instead of wrecking the factorization. Only the
+/// lower triangle of `A` is read.
+#[derive(Clone, Debug)]
+pub struct LDLT {
+    n : usize,
+    l : Vec<f64>,
+    // the diagonal of D and, at the first index of each 2x2 block, its
+    // off-diagonal entry
+    d : Vec<f64>,
+    off : Vec<f64>,
+    block : Vec<bool>,
+    perm : Vec<usize>,
+}
+
+/// Swaps `p < q` symmetrically in the working matrix and in the columns of
+/// `L` computed so far.
+fn symmetric_swap(n : usize, a : &mut [f64], l : &mut [f64], perm : &mut [usize], k : usize, p : usize, q : usize) {
+    if p == q {
+        return;
+    }
+    for j in 0..n {
+        a.swap(p * n + j, q * n + j);
+    }
+    for i in 0..n {
+        a.swap(i * n + p, i * n + q);
+    }
+    for j in 0..k {
+        l.swap(p * n + j, q * n + j);
+    }
+    perm.swap(p, q);
+}
+
+impl LDLT {
+    pub fn new(m : &DenseMatrix<f64>) -> LDLT {
+        LDLT::try_new(m).unwrap_or_else(|e| panic!("ldlt error : {}", e))
+    }
+
+    /// Fails with `Singular` only when a whole column of the remaining
+    /// Schur complement is zero (or a 2x2 block has a zero determinant),
+    /// which makes `A` singular.
+    pub fn try_new(m : &DenseMatrix<f64>) -> Result<LDLT, MatrixError> {
+        check_square((m.get_row(), m.get_column()))?;
+        let n = m.get_row();
+        let src = m.as_slice();
+        // a full symmetric working copy, updated to the Schur complement
+        let mut a = vec![0.0; n * n];
+        for i in 0..n {
+            for j in 0..=i {
+                a[i * n + j] = src[i * n + j];
+                a[j * n + i] = src[i * n + j];
+            }
+        }
+        let mut l = vec![0.0; n * n];
+        let mut d = vec![0.0; n];
+        let mut off = vec![0.0; n];
+        let mut block = vec![false; n];
+        let mut perm : Vec<usize> = (0..n).collect();
+        let mut k = 0;
+        while k < n {
+            let diagonal = a[k * n + k].abs();
+            let (mut imax, mut colmax) = (k, 0.0);
+            for i in (k + 1)..n {
+                if a[i * n + k].abs() > colmax {
+                    imax = i;
+                    colmax = a[i * n + k].abs();
+                }
+            }
+            if diagonal == 0.0 && colmax == 0.0 {
+                return Err(MatrixError::Singular);
+            }
+            let two = if diagonal >= BUNCH_KAUFMAN_ALPHA * colmax {
+                false
+            } else {
+                let rowmax = (k..n).filter(|&j| j != imax).map(|j| a[imax * n + j].abs()).fold(0.0, f64::max);
+                if diagonal * rowmax >= BUNCH_KAUFMAN_ALPHA * colmax * colmax {
+                    false
+                } else if a[imax * n + imax].abs() >= BUNCH_KAUFMAN_ALPHA * rowmax {
+                    symmetric_swap(n, &mut a, &mut l, &mut perm, k, k, imax);
+                    false
+                } else {
+                    symmetric_swap(n, &mut a, &mut l, &mut perm, k, k + 1, imax);
+                    true
+                }
+            };
+            if !two {
+                let dk = a[k * n + k];
+                d[k] = dk;
+                l[k * n + k] = 1.0;
+                for i in (k + 1)..n {
+                    l[i * n + k] = a[i * n + k] / dk;
+                }
+                for i in (k + 1)..n {
+                    for j in (k + 1)..=i {
+                        a[i * n + j] -= l[i * n + k] * a[j * n + k];
+                        a[j * n + i] = a[i * n + j];
+                    }
+                }
+                k += 1;
+            } else {
+                let (e11, e21, e22) = (a[k * n + k], a[(k + 1) * n + k], a[(k + 1) * n + k + 1]);
+                let det = e11 * e22 - e21 * e21;
+                if det == 0.0 {
+                    return Err(MatrixError::Singular);
+                }
+                d[k] = e11;
+                d[k + 1] = e22;
+                off[k] = e21;
+                block[k] = true;
+                l[k * n + k] = 1.0;
+                l[(k + 1) * n + k + 1] = 1.0;
+                // [l_ik, l_i,k+1] = [a_ik, a_i,k+1] * E⁻¹
+                for i in (k + 2)..n {
+                    let (c1, c2) = (a[i * n + k], a[i * n + k + 1]);
+                    l[i * n + k] = (c1 * e22 - c2 * e21) / det;
+                    l[i * n + k + 1] = (c2 * e11 - c1 * e21) / det;
+                }
+                for i in (k + 2)..n {
+                    for j in (k + 2)..=i {
+                        a[i * n + j] -= l[i * n + k] * a[j * n + k] + l[i * n + k + 1] * a[j * n + k + 1];
+                        a[j * n + i] = a[i * n + j];
+                    }
+                }
+                k += 2;
+            }
+        }
+        Ok(LDLT {
+            n : n,
+            l : l,
+            d : d,
+            off : off,
+            block : block,
+            perm : perm,
+        })
+    }
+
+    pub fn get_l(self : &Self) -> DenseMatrix<f64> {
+        lower_to_dense(self.n, &self.l, true)
+    }
+
+    /// The block diagonal factor, with 1x1 and symmetric 2x2 blocks.
+    pub fn get_d(self : &Self) -> DenseMatrix<f64> {
+        let mut m = DenseMatrix::new_with(self.n, self.n, 0.0);
+        for k in 0..self.n {
+            m.set(k, k, self.d[k]);
+            if self.block[k] {
+                m.set(k + 1, k, self.off[k]);
+                m.set(k, k + 1, self.off[k]);
+            }
+        }
+        m
+    }
+
+    /// Row `k` of `P * A * Pᵀ` is row `perm[k]` of `A`.
+    pub fn get_permutation(self : &Self) -> &[usize] {
+        &self.perm
+    }
+
+    pub fn solve(self : &Self, b : &Vector<f64>) -> Vector<f64> {
+        self.try_solve(b).unwrap_or_else(|e| panic!("ldlt solve error : {}", e))
+    }
+
+    pub fn try_solve(self : &Self, b : &Vector<f64>) -> Result<Vector<f64>, MatrixError> {
+        check_rhs(self.n, b)?;
+        let mut x : Vec<f64> = self.perm.iter().map(|&i| b[i]).collect();
+        forward_substitute(self.n, &self.l, true, &mut x);
+        let mut k = 0;
+        while k < self.n {
+            if self.block[k] {
+                let (e11, e21, e22) = (self.d[k], self.off[k], self.d[k + 1]);
+                let det = e11 * e22 - e21 * e21;
+                let (y1, y2) = (x[k], x[k + 1]);
+                x[k] = (e22 * y1 - e21 * y2) / det;
+                x[k + 1] = (e11 * y2 - e21 * y1) / det;
+                k += 2;
+            } else {
+                x[k] /= self.d[k];
+                k += 1;
+            }
+        }
+        backward_substitute(self.n, &self.l, true, &mut x);
+        let mut result = vec![0.0; self.n];
+        for (k, &i) in self.perm.iter().enumerate() {
+            result[i] = x[k];
+        }
+        Ok(Vector::from_vec(result))
+    }
+
+    /// The determinant of each block of `D`; `P` cancels out of `det(A)`.
+    fn block_determinants(self : &Self) -> Vec<f64> {
+        let mut dets = Vec::with_capacity(self.n);
+        let mut k = 0;
+        while k < self.n {
+            if self.block[k] {
+                dets.push(self.d[k] * self.d[k + 1] - self.off[k] * self.off[k]);
+                k += 2;
+            } else {
+                dets.push(self.d[k]);
+                k += 1;
+            }
+        }
+        dets
+    }
+
+    pub fn determinant(self : &Self) -> f64 {
+        self.block_determinants().iter().product()
+    }
+
+    /// `(sign, ln |det(A)|)`; the determinant of an indefinite matrix can be
+    /// negative, so the sign is returned separately.
+    pub fn log_determinant(self : &Self) -> (f64, f64) {
+        let dets = self.block_determinants();
+        let negative = dets.iter().filter(|&&v| v < 0.0).count();
+        let sign = if negative % 2 == 0 { 1.0 } else { -1.0 };
+        (sign, dets.iter().map(|v| v.abs().ln()).sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{matrix, vector};
+
+    fn assert_close(a : &DenseMatrix<f64>, b : &DenseMatrix<f64>) {
+        for (x, y) in a.as_slice().iter().zip(b.as_slice().iter()) {
+            assert!((x - y).abs() < 1e-10, "{} != {}", x, y);
+        }
+    }
+
+    #[test]
+    fn cholesky_spd() {
+        let a = matrix![DenseMatrix<f64> => (4.0, 12.0, -16.0);(12.0, 37.0, -43.0);(-16.0, -43.0, 98.0)];
+        let c = Cholesky::new(&a);
+        let l = c.get_l();
+        assert_eq!(l.as_slice(), &[2.0, 0.0, 0.0, 6.0, 1.0, 0.0, -8.0, 5.0, 3.0]);
+        assert_close(&(&l * &l.transpose()), &a);
+        assert!((c.determinant() - 36.0).abs() < 1e-9);
+        assert!((c.log_determinant() - 36.0f64.ln()).abs() < 1e-12);
+        let x = c.solve(&vector![1.0, 2.0, 3.0]);
+        for i in 0..3 {
+            let ax : f64 = (0..3).map(|j| a.get(i, j).unwrap() * x[j]).sum();
+            assert!((ax - (i + 1) as f64).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn cholesky_reports_failing_pivot() {
+        let a = matrix![DenseMatrix<f64> => (1.0, 2.0, 0.0);(2.0, 1.0, 0.0);(0.0, 0.0, 1.0)];
+        assert_eq!(Cholesky::try_new(&a).unwrap_err(), MatrixError::NotPositiveDefinite { pivot : 1 });
+    }
+
+    /// Checks `P * A * Pᵀ = L * D * Lᵀ` and that `D` has only 1x1 and 2x2
+    /// blocks.
+    fn assert_ldlt(a : &DenseMatrix<f64>, f : &LDLT) {
+        let n = a.get_row();
+        let (l, d, p) = (f.get_l(), f.get_d(), f.get_permutation());
+        let mut pap = DenseMatrix::new_with(n, n, 0.0);
+        for i in 0..n {
+            for j in 0..n {
+                pap.set(i, j, *a.get(p[i], p[j]).unwrap());
+            }
+            for j in (i + 2)..n {
+                assert_eq!(*d.get(j, i).unwrap(), 0.0);
+            }
+        }
+        assert_close(&(&(&l * &d) * &l.transpose()), &pap);
+    }
+
+    #[test]
+    fn ldlt_indefinite() {
+        let a = matrix![DenseMatrix<f64> => (1.0, 2.0, 0.0);(2.0, 1.0, 0.0);(0.0, 0.0, 3.0)];
+        let f = LDLT::new(&a);
+        assert_ldlt(&a, &f);
+        // the leading entry is too small against its column, so the first
+        // two rows form a 2x2 block
+        assert_eq!(*f.get_d().get(1, 0).unwrap(), 2.0);
+        assert!((f.determinant() + 9.0).abs() < 1e-12);
+        let (sign, log) = f.log_determinant();
+        assert_eq!(sign, -1.0);
+        assert!((log - 9.0f64.ln()).abs() < 1e-12);
+        let x = f.solve(&vector![3.0, 3.0, 3.0]);
+        assert!((x[0] - 1.0).abs() < 1e-12 && (x[1] - 1.0).abs() < 1e-12 && (x[2] - 1.0).abs() < 1e-12);
+        let z = matrix![DenseMatrix<f64> => (0.0, 1.0);(1.0, 0.0)];
+        let f = LDLT::new(&z);
+        assert_ldlt(&z, &f);
+        assert_eq!(f.determinant(), -1.0);
+        assert_eq!(f.solve(&vector![2.0, 3.0]), vector![3.0, 2.0]);
+        let singular = matrix![DenseMatrix<f64> => (1.0, 1.0, 0.0);(1.0, 1.0, 0.0);(0.0, 0.0, 2.0)];
+        assert_eq!(LDLT::try_new(&singular).unwrap_err(), MatrixError::Singular);
+    }
+
+    #[test]
+    fn ldlt_tiny_pivot() {
+        let a = matrix![DenseMatrix<f64> => (1e-20, 1.0);(1.0, 1.0)];
+        let f = LDLT::new(&a);
+        assert_ldlt(&a, &f);
+        assert_eq!(f.get_permutation(), &[1, 0]);
+        let x = f.solve(&vector![1.0, 2.0]);
+        assert!((x[0] - 1.0).abs() < 1e-12 && (x[1] - 1.0).abs() < 1e-12, "{} {}", x[0], x[1]);
+        // a larger indefinite matrix mixing both block sizes
+        let b = matrix![DenseMatrix<f64> => (1e-14, 2.0, 1.0, 0.0);(2.0, 0.0, 3.0, 1.0);(1.0, 3.0, -1.0, 2.0);(0.0, 1.0, 2.0, 5.0)];
+        let f = LDLT::new(&b);
+        assert_ldlt(&b, &f);
+        let x = f.solve(&vector![1.0, 2.0, 3.0, 4.0]);
+        for i in 0..4 {
+            let bx : f64 = (0..4).map(|j| b.get(i, j).unwrap() * x[j]).sum();
+            assert!((bx - (i + 1) as f64).abs() < 1e-10, "{} != {}", bx, i + 1);
+        }
+    }
+}
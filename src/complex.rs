@@ -0,0 +1,151 @@
+use std::ops::{Add, Sub, Mul, Div, Neg};
+use std::fmt::{Formatter, Display};
+
+use crate::matrix_base::*;
+
+/// A complex number `re + im * i`. It is a ring over a ring and a field over
+/// a field.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex<T> {
+    re : T,
+    im : T,
+}
+
+impl<T> Complex<T> {
+    pub fn new(re : T, im : T) -> Complex<T> {
+        Complex {
+            re : re,
+            im : im,
+        }
+    }
+}
+
+impl<T : Copy> Complex<T> {
+    pub fn get_re(self : &Self) -> T {
+        self.re
+    }
+
+    pub fn get_im(self : &Self) -> T {
+        self.im
+    }
+}
+
+impl<T : Copy + Ring> Complex<T> {
+    pub fn conj(self : &Self) -> Complex<T> {
+        Complex::new(self.re, self.im.get_inverse_add())
+    }
+}
+
+impl<T : Copy + Add<Output = T> + Mul<Output = T>> Complex<T> {
+    /// |z|², which stays in `T` (no square root needed).
+    pub fn norm_sqr(self : &Self) -> T {
+        self.re * self.re + self.im * self.im
+    }
+}
+
+impl<T : Add<Output = T>> Add for Complex<T> {
+    type Output = Complex<T>;
+    fn add(self : Self, other : Self) -> Self::Output {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl<T : Sub<Output = T>> Sub for Complex<T> {
+    type Output = Complex<T>;
+    fn sub(self : Self, other : Self) -> Self::Output {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl<T : Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Mul for Complex<T> {
+    type Output = Complex<T>;
+    fn mul(self : Self, other : Self) -> Self::Output {
+        Complex::new(self.re * other.re - self.im * other.im,
+                     self.re * other.im + self.im * other.re)
+    }
+}
+
+impl<T : Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>> Div for Complex<T> {
+    type Output = Complex<T>;
+    fn div(self : Self, other : Self) -> Self::Output {
+        let d = other.norm_sqr();
+        Complex::new((self.re * other.re + self.im * other.im) / d,
+                     (self.im * other.re - self.re * other.im) / d)
+    }
+}
+
+impl<T : Neg<Output = T>> Neg for Complex<T> {
+    type Output = Complex<T>;
+    fn neg(self : Self) -> Self::Output {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+impl<T : AddMonoid + Copy> AddMonoid for Complex<T> {
+    fn get_identity_add() -> Complex<T> {
+        Complex::new(T::get_identity_add(), T::get_identity_add())
+    }
+}
+
+impl<T : Ring + Copy> Semiring for Complex<T> {
+    fn get_identity_mul() -> Complex<T> {
+        Complex::new(T::get_identity_mul(), T::get_identity_add())
+    }
+}
+
+impl<T : Ring + Copy> Ring for Complex<T> {}
+
+// 1 / z = conj(z) / |z|², which is what the default `get_inverse_mul` computes
+// through `Div`.
+impl<T : Field + Copy> Field for Complex<T> {}
+
+impl<T : Magnitude> Magnitude for Complex<T> {
+    fn magnitude(self : &Self) -> f64 {
+        self.re.magnitude().hypot(self.im.magnitude())
+    }
+}
+
+impl<T : Display> Display for Complex<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({} + {}i)", self.re, self.im)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dense_matrix::DenseMatrix;
+    use crate::matrix;
+
+    #[test]
+    fn complex_arithmetic() {
+        let a = Complex::new(1.0, 2.0);
+        let b = Complex::new(3.0, -1.0);
+        assert_eq!(a + b, Complex::new(4.0, 1.0));
+        assert_eq!(a - b, Complex::new(-2.0, 3.0));
+        assert_eq!(a * b, Complex::new(5.0, 5.0));
+        assert_eq!((a * b) / b, a);
+        assert_eq!(a.conj(), Complex::new(1.0, -2.0));
+        assert_eq!(a.norm_sqr(), 5.0);
+    }
+
+    #[test]
+    fn complex_inverse_mul() {
+        let a : Complex<f64> = Complex::new(0.0, 2.0);
+        assert_eq!(a.get_inverse_mul(), Complex::new(0.0, -0.5));
+        assert_eq!(a * a.get_inverse_mul(), Complex::get_identity_mul());
+    }
+
+    #[test]
+    fn complex_matrix_inverse() {
+        let i = Complex::new(0.0, 1.0);
+        let one = Complex::new(1.0, 0.0);
+        let zero = Complex::new(0.0, 0.0);
+        let m = matrix![DenseMatrix<Complex<f64>> => (one, i);(zero, one)];
+        let inv = m.inverse().unwrap();
+        assert_eq!(*inv.get(0, 0).unwrap(), one);
+        assert_eq!(*inv.get(0, 1).unwrap(), -i);
+        assert_eq!(*inv.get(1, 0).unwrap(), zero);
+        assert_eq!(*inv.get(1, 1).unwrap(), one);
+    }
+}
@@ -0,0 +1,272 @@
+use std::ops::Mul;
+use std::fmt::{Formatter, Display};
+
+use crate::matrix_base::*;
+use crate::sparse_matrix::SparseMatrix;
+use crate::csr_matrix::{CsrMatrix, check_compressed, transpose_compressed};
+use crate::vector::Vector;
+use crate::error::*;
+
+pub struct IterItem<'a, T>(usize, usize, &'a T);
+
+impl<'a, T> IterItem<'a, T> {
+    pub fn get_row(self : &Self) -> usize {
+        self.0
+    }
+
+    pub fn get_col(self : &Self) -> usize {
+        self.1
+    }
+
+    pub fn get_v(self : &Self) -> &T {
+        self.2
+    }
+}
+
+pub struct ColumnIterator<'a, T> {
+    col : usize,
+    real_index : usize,
+    holder : &'a CscMatrix<T>,
+}
+
+impl<'a, T> Iterator for ColumnIterator<'a, T> {
+    type Item = IterItem<'a, T>;
+    fn next(self : &mut Self) -> Option<Self::Item> {
+        if self.real_index == self.holder.col_ptr[self.col + 1] {
+            None
+        } else {
+            let v = IterItem(self.holder.row_indices[self.real_index], self.col, &self.holder.values[self.real_index]);
+            self.real_index += 1;
+            Some(v)
+        }
+    }
+}
+
+/// Compressed Sparse Column storage, the column-major twin of `CsrMatrix`.
+///
+/// The entries of column `j` are `row_indices[col_ptr[j]..col_ptr[j + 1]]`
+/// (strictly increasing) with the matching `values`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CscMatrix<T> {
+    row : usize,
+    col : usize,
+    col_ptr : Vec<usize>,
+    row_indices : Vec<usize>,
+    values : Vec<T>,
+}
+
+impl<T> CscMatrix<T> {
+    pub fn new(row : usize, col : usize, col_ptr : Vec<usize>, row_indices : Vec<usize>, values : Vec<T>) -> CscMatrix<T> {
+        CscMatrix::try_new(row, col, col_ptr, row_indices, values).unwrap_or_else(|e| panic!("csc matrix error : {}", e))
+    }
+
+    pub fn try_new(row : usize, col : usize, col_ptr : Vec<usize>, row_indices : Vec<usize>, values : Vec<T>) -> Result<CscMatrix<T>, MatrixError> {
+        check_compressed(col, row, &col_ptr, &row_indices, values.len())?;
+        Ok(CscMatrix {
+            row : row,
+            col : col,
+            col_ptr : col_ptr,
+            row_indices : row_indices,
+            values : values,
+        })
+    }
+
+    pub fn nnz(self : &Self) -> usize {
+        self.values.len()
+    }
+
+    pub fn get_col_ptr(self : &Self) -> &[usize] {
+        &self.col_ptr
+    }
+
+    pub fn get_row_indices(self : &Self) -> &[usize] {
+        &self.row_indices
+    }
+
+    pub fn get_values(self : &Self) -> &[T] {
+        &self.values
+    }
+
+    pub fn get_column_iterator(self : &Self, col : usize) -> ColumnIterator<'_, T> {
+        ColumnIterator {
+            col : col,
+            real_index : self.col_ptr[col],
+            holder : self,
+        }
+    }
+}
+
+impl<T : Clone> CscMatrix<T> {
+    /// The CSC form of the transpose.
+    pub fn transpose(self : &Self) -> CscMatrix<T> {
+        let (col_ptr, row_indices, values) = transpose_compressed(self.col, self.row, &self.col_ptr, &self.row_indices, &self.values);
+        CscMatrix {
+            row : self.col,
+            col : self.row,
+            col_ptr : col_ptr,
+            row_indices : row_indices,
+            values : values,
+        }
+    }
+}
+
+impl<T : Clone> ConstMatrix<T> for CscMatrix<T> {
+    fn get_row(self : &Self) -> usize {
+        self.row
+    }
+
+    fn get_column(self : &Self) -> usize {
+        self.col
+    }
+
+    fn get(self : &Self, row : usize, col : usize) -> Option<&T> {
+        let begin = self.col_ptr[col];
+        let end = self.col_ptr[col + 1];
+        self.row_indices[begin..end].binary_search(&row).ok().map(|i| &self.values[begin + i])
+    }
+
+    fn get_sub_matrix(&self, row_begin : usize, row : usize, col_begin : usize, col : usize) -> Self {
+        let mut col_ptr = vec![0];
+        let mut row_indices = Vec::new();
+        let mut values = Vec::new();
+        for j in col_begin..(col_begin + col) {
+            for k in self.col_ptr[j]..self.col_ptr[j + 1] {
+                let i = self.row_indices[k];
+                if i >= row_begin && i < row_begin + row {
+                    row_indices.push(i - row_begin);
+                    values.push(self.values[k].clone());
+                }
+            }
+            col_ptr.push(row_indices.len());
+        }
+        CscMatrix::new(row, col, col_ptr, row_indices, values)
+    }
+}
+
+impl<T : Display> Display for CscMatrix<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cscmatrix[{}, {}] : \n", self.row, self.col).unwrap();
+        for col in 0..self.col {
+            for v in self.get_column_iterator(col) {
+                write!(f, "[{}, {}] = {} ", v.get_row(), v.get_col(), v.get_v()).unwrap();
+            }
+            write!(f, "\n").unwrap();
+        }
+        write!(f, "")
+    }
+}
+
+impl<T : Semiring + Copy> CscMatrix<T> {
+    /// `self * v`, scattering each column scaled by the matching entry of `v`.
+    pub fn try_mul_vector(self : &Self, v : &Vector<T>) -> Result<Vector<T>, MatrixError> {
+        if v.length() != self.col {
+            return Err(MatrixError::DimensionMismatch { left : (self.row, self.col), right : (v.length(), 1) });
+        }
+        let mut result = Vector::new_with(self.row, T::get_identity_add());
+        for j in 0..self.col {
+            for k in self.col_ptr[j]..self.col_ptr[j + 1] {
+                let i = self.row_indices[k];
+                result.set(i, result[i] + self.values[k] * v[j]);
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn mul_vector(self : &Self, v : &Vector<T>) -> Vector<T> {
+        self.try_mul_vector(v).unwrap_or_else(|e| panic!("matrix vector multiplication error : {}", e))
+    }
+}
+
+impl<'a, T : Semiring + Copy> Mul<&'a Vector<T>> for &'a CscMatrix<T> {
+    type Output = Vector<T>;
+    fn mul(self : Self, v : &'a Vector<T>) -> Self::Output {
+        self.mul_vector(v)
+    }
+}
+
+impl<'a, T : Clone> From<&'a CsrMatrix<T>> for CscMatrix<T> {
+    fn from(m : &'a CsrMatrix<T>) -> CscMatrix<T> {
+        let (col_ptr, row_indices, values) = transpose_compressed(m.get_row(), m.get_column(), m.get_row_ptr(), m.get_col_indices(), m.get_values());
+        CscMatrix {
+            row : m.get_row(),
+            col : m.get_column(),
+            col_ptr : col_ptr,
+            row_indices : row_indices,
+            values : values,
+        }
+    }
+}
+
+impl<'a, T : Clone> From<&'a CscMatrix<T>> for CsrMatrix<T> {
+    fn from(m : &'a CscMatrix<T>) -> CsrMatrix<T> {
+        let (row_ptr, col_indices, values) = transpose_compressed(m.col, m.row, &m.col_ptr, &m.row_indices, &m.values);
+        CsrMatrix::new(m.row, m.col, row_ptr, col_indices, values)
+    }
+}
+
+impl<'a, T : Clone + Default> From<&'a SparseMatrix<T>> for CscMatrix<T> {
+    fn from(m : &'a SparseMatrix<T>) -> CscMatrix<T> {
+        CscMatrix::from(&CsrMatrix::from(m))
+    }
+}
+
+impl<'a, T : Clone + Default> From<&'a CscMatrix<T>> for SparseMatrix<T> {
+    fn from(m : &'a CscMatrix<T>) -> SparseMatrix<T> {
+        SparseMatrix::from(&CsrMatrix::from(m))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector;
+
+    fn sample() -> SparseMatrix<f64> {
+        // [[1, 0, 2],
+        //  [0, 0, 0],
+        //  [0, 3, 4]]
+        let mut m = SparseMatrix::new(3, 3);
+        m.set(0, 0, 1.0);
+        m.set(0, 2, 2.0);
+        m.set(2, 1, 3.0);
+        m.set(2, 2, 4.0);
+        m
+    }
+
+    #[test]
+    fn csc_from_sparse() {
+        let c = CscMatrix::from(&sample());
+        assert_eq!(c.get_col_ptr(), &[0, 1, 2, 4]);
+        assert_eq!(c.get_row_indices(), &[0, 2, 0, 2]);
+        assert_eq!(c.get_values(), &[1.0, 3.0, 2.0, 4.0]);
+        assert_eq!(c.get(0, 2), Some(&2.0));
+        assert_eq!(c.get(1, 2), None);
+        let col : Vec<(usize, f64)> = c.get_column_iterator(2).map(|x| (x.get_row(), *x.get_v())).collect();
+        assert_eq!(col, vec![(0, 2.0), (2, 4.0)]);
+        assert_eq!(CsrMatrix::from(&c), CsrMatrix::from(&sample()));
+        assert_eq!(SparseMatrix::from(&c).get(2, 1), Some(&3.0));
+    }
+
+    #[test]
+    fn csc_transpose() {
+        let csr = CsrMatrix::from(&sample());
+        let t = csr.transpose();
+        assert_eq!(t.get(2, 0), Some(&2.0));
+        assert_eq!(t.get(1, 2), Some(&3.0));
+        assert_eq!(t.transpose(), csr);
+        let c = CscMatrix::from(&csr);
+        assert_eq!(c.transpose(), CscMatrix::from(&t));
+    }
+
+    #[test]
+    fn csc_mul_vector() {
+        let c = CscMatrix::from(&sample());
+        assert_eq!(&c * &vector![1.0, 2.0, 3.0], vector![7.0, 0.0, 18.0]);
+    }
+
+    #[test]
+    fn csc_invalid_storage() {
+        assert!(CscMatrix::try_new(2, 2, vec![0, 1, 2], vec![1, 0], vec![1.0, 2.0]).is_ok());
+        assert_eq!(CscMatrix::try_new(2, 2, vec![0, 5, 2], vec![0, 1], vec![1.0, 2.0]).unwrap_err(), MatrixError::InvalidStorage { reason : "pointer array is not sorted" });
+    }
+}
@@ -0,0 +1,337 @@
+use std::ops::Mul;
+use std::fmt::{Formatter, Display};
+
+use crate::matrix_base::*;
+use crate::sparse_matrix::SparseMatrix;
+use crate::vector::Vector;
+use crate::error::*;
+
+pub struct IterItem<'a, T>(usize, usize, &'a T);
+
+impl<'a, T> IterItem<'a, T> {
+    pub fn get_row(self : &Self) -> usize {
+        self.0
+    }
+
+    pub fn get_col(self : &Self) -> usize {
+        self.1
+    }
+
+    pub fn get_v(self : &Self) -> &T {
+        self.2
+    }
+}
+
+pub struct RowIterator<'a, T> {
+    row : usize,
+    real_index : usize,
+    holder : &'a CsrMatrix<T>,
+}
+
+impl<'a, T> Iterator for RowIterator<'a, T> {
+    type Item = IterItem<'a, T>;
+    fn next(self : &mut Self) -> Option<Self::Item> {
+        if self.real_index == self.holder.row_ptr[self.row + 1] {
+            None
+        } else {
+            let v = IterItem(self.row, self.holder.col_indices[self.real_index], &self.holder.values[self.real_index]);
+            self.real_index += 1;
+            Some(v)
+        }
+    }
+}
+
+/// Compressed Sparse Row storage.
+///
+/// The entries of row `i` are `col_indices[row_ptr[i]..row_ptr[i + 1]]`
+/// (strictly increasing) with the matching `values`. The structure is
+/// immutable: assemble with `SparseMatrix`, then convert for fast reads.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsrMatrix<T> {
+    row : usize,
+    col : usize,
+    row_ptr : Vec<usize>,
+    col_indices : Vec<usize>,
+    values : Vec<T>,
+}
+
+impl<T> CsrMatrix<T> {
+    pub fn new(row : usize, col : usize, row_ptr : Vec<usize>, col_indices : Vec<usize>, values : Vec<T>) -> CsrMatrix<T> {
+        CsrMatrix::try_new(row, col, row_ptr, col_indices, values).unwrap_or_else(|e| panic!("csr matrix error : {}", e))
+    }
+
+    pub fn try_new(row : usize, col : usize, row_ptr : Vec<usize>, col_indices : Vec<usize>, values : Vec<T>) -> Result<CsrMatrix<T>, MatrixError> {
+        check_compressed(row, col, &row_ptr, &col_indices, values.len())?;
+        Ok(CsrMatrix {
+            row : row,
+            col : col,
+            row_ptr : row_ptr,
+            col_indices : col_indices,
+            values : values,
+        })
+    }
+
+    pub fn nnz(self : &Self) -> usize {
+        self.values.len()
+    }
+
+    pub fn get_row_ptr(self : &Self) -> &[usize] {
+        &self.row_ptr
+    }
+
+    pub fn get_col_indices(self : &Self) -> &[usize] {
+        &self.col_indices
+    }
+
+    pub fn get_values(self : &Self) -> &[T] {
+        &self.values
+    }
+
+    fn find(self : &Self, row : usize, col : usize) -> Option<usize> {
+        let begin = self.row_ptr[row];
+        let end = self.row_ptr[row + 1];
+        self.col_indices[begin..end].binary_search(&col).ok().map(|i| begin + i)
+    }
+}
+
+/// Checks the invariants shared by CSR and CSC: `ptr` has `major + 1`
+/// non-decreasing entries from 0 to nnz, and every major slice of `indices`
+/// is strictly increasing and below `minor`.
+pub(crate) fn check_compressed(major : usize, minor : usize, ptr : &[usize], indices : &[usize], nnz : usize) -> Result<(), MatrixError> {
+    if ptr.len() != major + 1 || ptr[0] != 0 || ptr[major] != indices.len() {
+        return Err(MatrixError::InvalidStorage { reason : "pointer array does not span the index array" });
+    }
+    if indices.len() != nnz {
+        return Err(MatrixError::InvalidStorage { reason : "index and value arrays differ in length" });
+    }
+    // with the ends pinned to 0 and nnz this keeps every slice in bounds
+    if ptr.windows(2).any(|w| w[0] > w[1]) {
+        return Err(MatrixError::InvalidStorage { reason : "pointer array is not sorted" });
+    }
+    for i in 0..major {
+        let slice = &indices[ptr[i]..ptr[i + 1]];
+        if slice.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(MatrixError::InvalidStorage { reason : "indices are not strictly increasing" });
+        }
+        if slice.last().is_some_and(|&last| last >= minor) {
+            return Err(MatrixError::InvalidStorage { reason : "index out of range" });
+        }
+    }
+    Ok(())
+}
+
+/// Re-compresses along the other axis with one counting sort: the CSR
+/// arrays of a matrix become the CSC arrays of the same matrix (equally, the
+/// CSR arrays of its transpose). Indices come out sorted because the major
+/// axis is walked in order. O(nnz + minor).
+pub(crate) fn transpose_compressed<T : Clone>(major : usize, minor : usize, ptr : &[usize], indices : &[usize], values : &[T]) -> (Vec<usize>, Vec<usize>, Vec<T>) {
+    let nnz = indices.len();
+    let mut t_ptr = vec![0; minor + 1];
+    for &j in indices.iter() {
+        t_ptr[j + 1] += 1;
+    }
+    for j in 0..minor {
+        t_ptr[j + 1] += t_ptr[j];
+    }
+    let mut next = t_ptr.clone();
+    let mut t_indices = vec![0; nnz];
+    let mut t_values : Vec<Option<T>> = vec![None; nnz];
+    for i in 0..major {
+        for k in ptr[i]..ptr[i + 1] {
+            let j = indices[k];
+            t_indices[next[j]] = i;
+            t_values[next[j]] = Some(values[k].clone());
+            next[j] += 1;
+        }
+    }
+    (t_ptr, t_indices, t_values.into_iter().map(|v| v.unwrap()).collect())
+}
+
+impl<T : Clone> CsrMatrix<T> {
+    /// The CSR form of the transpose.
+    pub fn transpose(self : &Self) -> CsrMatrix<T> {
+        let (row_ptr, col_indices, values) = transpose_compressed(self.row, self.col, &self.row_ptr, &self.col_indices, &self.values);
+        CsrMatrix {
+            row : self.col,
+            col : self.row,
+            row_ptr : row_ptr,
+            col_indices : col_indices,
+            values : values,
+        }
+    }
+}
+
+impl<T : Clone> ConstMatrix<T> for CsrMatrix<T> {
+    fn get_row(self : &Self) -> usize {
+        self.row
+    }
+
+    fn get_column(self : &Self) -> usize {
+        self.col
+    }
+
+    fn get(self : &Self, row : usize, col : usize) -> Option<&T> {
+        self.find(row, col).map(|i| &self.values[i])
+    }
+
+    fn get_sub_matrix(&self, row_begin : usize, row : usize, col_begin : usize, col : usize) -> Self {
+        let mut row_ptr = vec![0];
+        let mut col_indices = Vec::new();
+        let mut values = Vec::new();
+        for i in row_begin..(row_begin + row) {
+            for k in self.row_ptr[i]..self.row_ptr[i + 1] {
+                let j = self.col_indices[k];
+                if j >= col_begin && j < col_begin + col {
+                    col_indices.push(j - col_begin);
+                    values.push(self.values[k].clone());
+                }
+            }
+            row_ptr.push(col_indices.len());
+        }
+        CsrMatrix::new(row, col, row_ptr, col_indices, values)
+    }
+}
+
+impl<'a, T> MatrixIterator<'a, RowIterator<'a, T>> for CsrMatrix<T> {
+    fn get_iterator<'b : 'a>(self : &'b Self, row : usize) -> RowIterator<'a, T> {
+        RowIterator {
+            row : row,
+            real_index : self.row_ptr[row],
+            holder : self,
+        }
+    }
+}
+
+impl<T : Display> Display for CsrMatrix<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "csrmatrix[{}, {}] : \n", self.row, self.col).unwrap();
+        for row in 0..self.row {
+            for v in self.get_iterator(row) {
+                write!(f, "[{}, {}] = {} ", v.get_row(), v.get_col(), v.get_v()).unwrap();
+            }
+            write!(f, "\n").unwrap();
+        }
+        write!(f, "")
+    }
+}
+
+impl<T : Semiring + Copy> CsrMatrix<T> {
+    /// `self * v`, one pass over the stored entries.
+    pub fn try_mul_vector(self : &Self, v : &Vector<T>) -> Result<Vector<T>, MatrixError> {
+        if v.length() != self.col {
+            return Err(MatrixError::DimensionMismatch { left : (self.row, self.col), right : (v.length(), 1) });
+        }
+        let mut result = Vector::new_with(self.row, T::get_identity_add());
+        for i in 0..self.row {
+            let mut sum = T::get_identity_add();
+            for k in self.row_ptr[i]..self.row_ptr[i + 1] {
+                sum = sum + self.values[k] * v[self.col_indices[k]];
+            }
+            result.set(i, sum);
+        }
+        Ok(result)
+    }
+
+    pub fn mul_vector(self : &Self, v : &Vector<T>) -> Vector<T> {
+        self.try_mul_vector(v).unwrap_or_else(|e| panic!("matrix vector multiplication error : {}", e))
+    }
+}
+
+impl<'a, T : Semiring + Copy> Mul<&'a Vector<T>> for &'a CsrMatrix<T> {
+    type Output = Vector<T>;
+    fn mul(self : Self, v : &'a Vector<T>) -> Self::Output {
+        self.mul_vector(v)
+    }
+}
+
+impl<'a, T : Clone + Default> From<&'a SparseMatrix<T>> for CsrMatrix<T> {
+    fn from(m : &'a SparseMatrix<T>) -> CsrMatrix<T> {
+        let mut row_ptr = Vec::with_capacity(m.get_row() + 1);
+        let mut col_indices = Vec::with_capacity(m.nnz());
+        let mut values = Vec::with_capacity(m.nnz());
+        row_ptr.push(0);
+        for i in 0..m.get_row() {
+            for each in m.get_iterator(i) {
+                col_indices.push(each.get_col());
+                values.push(each.get_v().clone());
+            }
+            row_ptr.push(col_indices.len());
+        }
+        CsrMatrix {
+            row : m.get_row(),
+            col : m.get_column(),
+            row_ptr : row_ptr,
+            col_indices : col_indices,
+            values : values,
+        }
+    }
+}
+
+impl<'a, T : Clone + Default> From<&'a CsrMatrix<T>> for SparseMatrix<T> {
+    fn from(m : &'a CsrMatrix<T>) -> SparseMatrix<T> {
+        let mut s = SparseMatrix::new(m.row, m.col);
+        for i in 0..m.row {
+            for k in m.row_ptr[i]..m.row_ptr[i + 1] {
+                s.push_sorted(i, m.col_indices[k], m.values[k].clone());
+            }
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector;
+
+    fn sample() -> SparseMatrix<f64> {
+        // [[1, 0, 2],
+        //  [0, 0, 0],
+        //  [0, 3, 4]]
+        let mut m = SparseMatrix::new(3, 3);
+        m.set(0, 2, 2.0);
+        m.set(0, 0, 1.0);
+        m.set(2, 1, 3.0);
+        m.set(2, 2, 4.0);
+        m
+    }
+
+    #[test]
+    fn csr_from_sparse() {
+        let c = CsrMatrix::from(&sample());
+        assert_eq!(c.get_row_ptr(), &[0, 2, 2, 4]);
+        assert_eq!(c.get_col_indices(), &[0, 2, 1, 2]);
+        assert_eq!(c.get_values(), &[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(c.get(2, 1), Some(&3.0));
+        assert_eq!(c.get(1, 1), None);
+        assert_eq!(c.get_or_zero(1, 1), 0.0);
+        let back = SparseMatrix::from(&c);
+        assert_eq!(CsrMatrix::from(&back), c);
+    }
+
+    #[test]
+    fn csr_mul_vector() {
+        let c = CsrMatrix::from(&sample());
+        let v = &c * &vector![1.0, 2.0, 3.0];
+        assert_eq!(v, vector![7.0, 0.0, 18.0]);
+        assert!(c.try_mul_vector(&vector![1.0]).is_err());
+    }
+
+    #[test]
+    fn csr_sub_matrix() {
+        let c = CsrMatrix::from(&sample());
+        let s = c.get_sub_matrix(1, 2, 1, 2);
+        assert_eq!(s.get_row_ptr(), &[0, 0, 2]);
+        assert_eq!(s.get(1, 0), Some(&3.0));
+    }
+
+    #[test]
+    fn csr_invalid_storage() {
+        assert!(CsrMatrix::try_new(2, 2, vec![0, 1, 2], vec![1, 0], vec![1.0, 2.0]).is_ok());
+        assert!(CsrMatrix::try_new(2, 2, vec![0, 2, 2], vec![1, 0], vec![1.0, 2.0]).is_err());
+        assert!(CsrMatrix::try_new(2, 2, vec![0, 1, 2], vec![1, 2], vec![1.0, 2.0]).is_err());
+        assert!(CsrMatrix::try_new(2, 2, vec![0, 1], vec![1], vec![1.0]).is_err());
+        // an unsorted pointer that would slice past the index array
+        assert_eq!(CsrMatrix::try_new(2, 2, vec![0, 5, 2], vec![0, 1], vec![1.0, 2.0]).unwrap_err(), MatrixError::InvalidStorage { reason : "pointer array is not sorted" });
+    }
+}
@@ -1,9 +1,9 @@
-use std::ops::Add;
-use std::ops::Mul;
+use std::ops::{Add, Sub, Mul, Neg, AddAssign, MulAssign};
 use std::fmt::{Formatter, Display};
 
 use crate::matrix_base::*;
 use crate::vector::*;
+use crate::error::*;
 
 pub struct Item<'a, T>(usize, usize, &'a T);
 
@@ -12,15 +12,15 @@ impl<'a, T> Item<'a, T> {
         Item(row, col, v)
     }
 
-    fn get_row(self : &Self) -> usize {
+    pub fn get_row(self : &Self) -> usize {
         self.0
     }
 
-    fn get_col(self : &Self) -> usize {
+    pub fn get_col(self : &Self) -> usize {
         self.1
     }
 
-    fn get_v(self : &Self) -> &T {
+    pub fn get_v(self : &Self) -> &T {
         self.2
     }
 }
@@ -55,7 +55,7 @@ impl<'a, T> Iterator for RowIterator<'a, T> {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DenseMatrix<T> {
     row : usize,
     col : usize,
@@ -74,6 +74,57 @@ impl<T : Default + Clone> MatrixInit<T> for DenseMatrix<T> {
     }
 }
 
+impl<T : Clone> DenseMatrix<T> {
+    pub fn new_with(row : usize, col : usize, value : T) -> DenseMatrix<T> {
+        DenseMatrix {
+            row : row,
+            col : col,
+            container : vec![value; row * col],
+        }
+    }
+
+    pub fn transpose(self : &Self) -> DenseMatrix<T> {
+        let mut container = Vec::with_capacity(self.container.len());
+        for j in 0..self.col {
+            for i in 0..self.row {
+                container.push(self.container[self.get_index(i, j)].clone());
+            }
+        }
+        DenseMatrix {
+            row : self.col,
+            col : self.row,
+            container : container,
+        }
+    }
+}
+
+impl<T> DenseMatrix<T> {
+    /// Takes ownership of `container`, read as `row` rows of `col` elements.
+    pub fn from_vec(row : usize, col : usize, container : Vec<T>) -> DenseMatrix<T> {
+        DenseMatrix::try_from_vec(row, col, container).unwrap_or_else(|e| panic!("dense matrix error : {}", e))
+    }
+
+    pub fn try_from_vec(row : usize, col : usize, container : Vec<T>) -> Result<DenseMatrix<T>, MatrixError> {
+        if container.len() != row * col {
+            return Err(MatrixError::DimensionMismatch { left : (row, col), right : (container.len(), 1) });
+        }
+        Ok(DenseMatrix {
+            row : row,
+            col : col,
+            container : container,
+        })
+    }
+
+    /// The elements in row-major order.
+    pub fn as_slice(self : &Self) -> &[T] {
+        &self.container
+    }
+
+    pub fn into_vec(self : Self) -> Vec<T> {
+        self.container
+    }
+}
+
 impl<T : Default + Copy> ConstMatrix<T> for DenseMatrix<T> {
     fn get_row(self : &Self) -> usize {
         return self.row;
@@ -131,15 +182,22 @@ impl<'a, T> MatrixIterator<'a, RowIterator<'a, T>> for DenseMatrix<T> {
 }
 
 impl<T : Clone> DenseMatrix<T> {
-    #[warn(dead_code)]
     pub fn set_nth_column(self : &mut Self, col : usize, v : Vector<T>) {
+        if let Err(e) = self.try_set_nth_column(col, v) {
+            panic!("set column error : {}", e);
+        }
+    }
+
+    pub fn try_set_nth_column(self : &mut Self, col : usize, v : Vector<T>) -> Result<(), MatrixError> {
+        check_index(0, col, (1, self.col))?;
         if v.length() != self.row {
-            panic!("set column error, mismatch length !");
+            return Err(MatrixError::DimensionMismatch { left : (self.row, self.col), right : (v.length(), 1) });
         }
         for i in 0..self.row {
             let index = self.get_index(i, col);
             self.container[index] = v[i].clone();
         }
+        Ok(())
     }
 }
 
@@ -152,10 +210,15 @@ impl<T : Clone + Default> DenseMatrix<T> {
         }
         v
     }
+
+    pub fn try_get_nth_column(self : &Self, col : usize) -> Result<Vector<T>, MatrixError> {
+        check_index(0, col, (1, self.col))?;
+        Ok(self.get_nth_column(col))
+    }
 }
 
 impl<T> Matrix<T> for DenseMatrix<T>
-    where T: Default + Copy + Add<Output = T> + Mul<Output = T> + Display + Group<T> + PartialEq {
+    where T: Default + Copy + Display + Semiring {
     fn set(self : &mut Self, row : usize, col : usize, value : T) {
         let index = self.get_index(row, col);
         self.container[index] = value;
@@ -188,4 +251,244 @@ impl<T> Matrix<T> for DenseMatrix<T>
             self.container[i1] = self.container[i1] + self.container[i2] * k;
         }
     }
-}
\ No newline at end of file
+}
+
+impl<T : Semiring + Copy> DenseMatrix<T> {
+    fn check_same_shape(self : &Self, other : &Self) -> Result<(), MatrixError> {
+        if self.row != other.row || self.col != other.col {
+            return Err(MatrixError::DimensionMismatch { left : (self.row, self.col), right : (other.row, other.col) });
+        }
+        Ok(())
+    }
+
+    fn zip_with<F : Fn(T, T) -> T>(self : &Self, other : &Self, f : F) -> DenseMatrix<T> {
+        DenseMatrix {
+            row : self.row,
+            col : self.col,
+            container : self.container.iter().zip(other.container.iter()).map(|(a, b)| f(*a, *b)).collect(),
+        }
+    }
+
+    /// Multiplies every element by `k` from the left.
+    pub fn scale(self : &Self, k : T) -> DenseMatrix<T> {
+        DenseMatrix {
+            row : self.row,
+            col : self.col,
+            container : self.container.iter().map(|v| k * *v).collect(),
+        }
+    }
+
+    /// `self + other`, or an error if the shapes differ.
+    pub fn try_add_matrix(self : &Self, other : &Self) -> Result<DenseMatrix<T>, MatrixError> {
+        self.check_same_shape(other)?;
+        Ok(self.zip_with(other, |a, b| a + b))
+    }
+
+    /// `self * other`, or an error if `self`'s column count is not `other`'s
+    /// row count.
+    pub fn try_mul_matrix(self : &Self, other : &Self) -> Result<DenseMatrix<T>, MatrixError> {
+        if self.col != other.row {
+            return Err(MatrixError::DimensionMismatch { left : (self.row, self.col), right : (other.row, other.col) });
+        }
+        let mut m = DenseMatrix::new_with(self.row, other.col, T::get_identity_add());
+        for i in 0..self.row {
+            for k in 0..self.col {
+                let a = self.container[self.get_index(i, k)];
+                for j in 0..other.col {
+                    let index = m.get_index(i, j);
+                    m.container[index] = m.container[index] + a * other.container[other.get_index(k, j)];
+                }
+            }
+        }
+        Ok(m)
+    }
+
+    fn product(self : &Self, other : &Self) -> DenseMatrix<T> {
+        self.try_mul_matrix(other).unwrap_or_else(|e| panic!("matrix multiplication error : {}", e))
+    }
+}
+
+impl<T : Ring + Copy> DenseMatrix<T> {
+    /// `self - other`, or an error if the shapes differ.
+    pub fn try_sub_matrix(self : &Self, other : &Self) -> Result<DenseMatrix<T>, MatrixError> {
+        self.check_same_shape(other)?;
+        Ok(self.zip_with(other, |a, b| a - b))
+    }
+}
+
+impl<'a, T : Semiring + Copy> Add<&'a DenseMatrix<T>> for &'a DenseMatrix<T> {
+    type Output = DenseMatrix<T>;
+    fn add(self : Self, other : Self) -> Self::Output {
+        self.try_add_matrix(other).unwrap_or_else(|e| panic!("matrix addition error : {}", e))
+    }
+}
+
+impl<T : Semiring + Copy> Add for DenseMatrix<T> {
+    type Output = DenseMatrix<T>;
+    fn add(self : Self, other : Self) -> Self::Output {
+        &self + &other
+    }
+}
+
+impl<'a, T : Ring + Copy> Sub<&'a DenseMatrix<T>> for &'a DenseMatrix<T> {
+    type Output = DenseMatrix<T>;
+    fn sub(self : Self, other : Self) -> Self::Output {
+        self.try_sub_matrix(other).unwrap_or_else(|e| panic!("matrix subtraction error : {}", e))
+    }
+}
+
+impl<T : Ring + Copy> Sub for DenseMatrix<T> {
+    type Output = DenseMatrix<T>;
+    fn sub(self : Self, other : Self) -> Self::Output {
+        &self - &other
+    }
+}
+
+impl<'a, T : Semiring + Copy> Mul<&'a DenseMatrix<T>> for &'a DenseMatrix<T> {
+    type Output = DenseMatrix<T>;
+    fn mul(self : Self, other : Self) -> Self::Output {
+        self.product(other)
+    }
+}
+
+impl<T : Semiring + Copy> Mul for DenseMatrix<T> {
+    type Output = DenseMatrix<T>;
+    fn mul(self : Self, other : Self) -> Self::Output {
+        self.product(&other)
+    }
+}
+
+impl<T : Ring + Copy> Neg for &DenseMatrix<T> {
+    type Output = DenseMatrix<T>;
+    fn neg(self : Self) -> Self::Output {
+        DenseMatrix {
+            row : self.row,
+            col : self.col,
+            container : self.container.iter().map(|v| -*v).collect(),
+        }
+    }
+}
+
+impl<T : Ring + Copy> Neg for DenseMatrix<T> {
+    type Output = DenseMatrix<T>;
+    fn neg(self : Self) -> Self::Output {
+        -&self
+    }
+}
+
+impl<'a, T : Semiring + Copy> AddAssign<&'a DenseMatrix<T>> for DenseMatrix<T> {
+    fn add_assign(self : &mut Self, other : &'a DenseMatrix<T>) {
+        if let Err(e) = self.check_same_shape(other) {
+            panic!("matrix addition error : {}", e);
+        }
+        for (a, b) in self.container.iter_mut().zip(other.container.iter()) {
+            *a = *a + *b;
+        }
+    }
+}
+
+impl<T : Semiring + Copy> AddAssign for DenseMatrix<T> {
+    fn add_assign(self : &mut Self, other : DenseMatrix<T>) {
+        *self += &other;
+    }
+}
+
+impl<'a, T : Semiring + Copy> MulAssign<&'a DenseMatrix<T>> for DenseMatrix<T> {
+    fn mul_assign(self : &mut Self, other : &'a DenseMatrix<T>) {
+        *self = self.product(other);
+    }
+}
+
+impl<T : Semiring + Copy> MulAssign for DenseMatrix<T> {
+    fn mul_assign(self : &mut Self, other : DenseMatrix<T>) {
+        *self = self.product(&other);
+    }
+}
+
+impl<T : Semiring + Copy> MulAssign<T> for DenseMatrix<T> {
+    fn mul_assign(self : &mut Self, k : T) {
+        for v in self.container.iter_mut() {
+            *v = k * *v;
+        }
+    }
+}
+
+// `k * m` has the scalar on the left, so it has to be implemented on the
+// scalar type itself; the orphan rule only allows that for concrete types.
+macro_rules! impl_scalar_mul {
+    ($($t : ty),+) => {$(
+        impl<'a> Mul<&'a DenseMatrix<$t>> for $t {
+            type Output = DenseMatrix<$t>;
+            fn mul(self : Self, m : &'a DenseMatrix<$t>) -> Self::Output {
+                m.scale(self)
+            }
+        }
+
+        impl Mul<DenseMatrix<$t>> for $t {
+            type Output = DenseMatrix<$t>;
+            fn mul(self : Self, m : DenseMatrix<$t>) -> Self::Output {
+                m.scale(self)
+            }
+        }
+    )+};
+}
+
+impl_scalar_mul!(f32, f64, i8, i16, i32, i64, i128, isize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix;
+
+    #[test]
+    fn dense_add_sub_neg() {
+        let a = matrix![DenseMatrix<i32> => (1, 2);(3, 4)];
+        let b = matrix![DenseMatrix<i32> => (4, 3);(2, 1)];
+        assert_eq!(&a + &b, matrix![DenseMatrix<i32> => (5, 5);(5, 5)]);
+        assert_eq!(&a - &b, matrix![DenseMatrix<i32> => (-3, -1);(1, 3)]);
+        assert_eq!(-a.clone(), matrix![DenseMatrix<i32> => (-1, -2);(-3, -4)]);
+        let mut c = a.clone();
+        c += b;
+        assert_eq!(c, matrix![DenseMatrix<i32> => (5, 5);(5, 5)]);
+    }
+
+    #[test]
+    fn dense_mul() {
+        let a = matrix![DenseMatrix<i32> => (1, 2, 3);(4, 5, 6)];
+        let b = matrix![DenseMatrix<i32> => (1, 0);(0, 1);(1, 1)];
+        let c = &a * &b;
+        assert_eq!(c, matrix![DenseMatrix<i32> => (4, 5);(10, 11)]);
+        let mut d = c.clone();
+        d *= &c;
+        assert_eq!(d, matrix![DenseMatrix<i32> => (66, 75);(150, 171)]);
+    }
+
+    #[test]
+    fn dense_scalar_mul() {
+        let a = matrix![DenseMatrix<f64> => (1.0, 2.0);(3.0, 4.0)];
+        assert_eq!(2.0 * &a, matrix![DenseMatrix<f64> => (2.0, 4.0);(6.0, 8.0)]);
+        let mut b = a.clone();
+        b *= 0.5;
+        assert_eq!(b, matrix![DenseMatrix<f64> => (0.5, 1.0);(1.5, 2.0)]);
+    }
+
+    #[test]
+    fn dense_try_operators() {
+        let a : DenseMatrix<i32> = DenseMatrix::new(2, 3);
+        let b : DenseMatrix<i32> = DenseMatrix::new(3, 2);
+        assert_eq!(a.try_add_matrix(&b).unwrap_err(), MatrixError::DimensionMismatch { left : (2, 3), right : (3, 2) });
+        assert_eq!(a.try_mul_matrix(&b).unwrap().get_row(), 2);
+        let mut c = a.clone();
+        assert!(c.try_set_nth_column(0, Vector::new(3)).is_err());
+        assert!(c.try_set_nth_column(3, Vector::new(2)).is_err());
+        assert!(c.try_get_nth_column(3).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "dimension mismatch")]
+    fn dense_mul_dimension_mismatch() {
+        let a : DenseMatrix<i32> = DenseMatrix::new(2, 3);
+        let b : DenseMatrix<i32> = DenseMatrix::new(2, 3);
+        let _ = &a * &b;
+    }
+}
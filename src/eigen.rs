@@ -0,0 +1,979 @@
+use crate::matrix_base::*;
+use crate::dense_matrix::DenseMatrix;
+use crate::vector::Vector;
+use crate::complex::Complex;
+use crate::linear_operator::LinearOperator;
+use crate::error::*;
+
+/// Matrices up to this order go through cyclic Jacobi in
+/// `SymmetricEigen::new`; larger ones are tridiagonalized first.
+const JACOBI_MAX_ORDER : usize = 16;
+const JACOBI_MAX_SWEEPS : usize = 64;
+const QL_MAX_ITERATIONS : usize = 64;
+const HQR_MAX_ITERATIONS : usize = 100;
+
+/// Copies the lower triangle of a square matrix into a symmetric `n x n`
+/// row array.
+fn symmetric_rows(m : &DenseMatrix<f64>) -> Result<Vec<Vec<f64>>, MatrixError> {
+    check_square((m.get_row(), m.get_column()))?;
+    let n = m.get_row();
+    let a = m.as_slice();
+    let mut rows = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            rows[i][j] = a[i * n + j];
+            rows[j][i] = a[i * n + j];
+        }
+    }
+    Ok(rows)
+}
+
+/// Eigendecomposition `A = V * diag(λ) * Vᵀ` of a real symmetric matrix.
+///
+/// Eigenvalues are in ascending order and column `k` of `V` is the unit
+/// eigenvector of `λ[k]`. Only the lower triangle of `A` is read.
+#[derive(Clone, Debug)]
+pub struct SymmetricEigen {
+    values : Vec<f64>,
+    vectors : Vec<Vec<f64>>,
+}
+
+impl SymmetricEigen {
+    /// Cyclic Jacobi for small matrices, Householder tridiagonalization plus
+    /// implicit QL for the rest.
+    pub fn new(m : &DenseMatrix<f64>) -> SymmetricEigen {
+        SymmetricEigen::try_new(m).unwrap_or_else(|e| panic!("symmetric eigen error : {}", e))
+    }
+
+    pub fn try_new(m : &DenseMatrix<f64>) -> Result<SymmetricEigen, MatrixError> {
+        if m.get_row() <= JACOBI_MAX_ORDER {
+            SymmetricEigen::try_jacobi(m)
+        } else {
+            SymmetricEigen::try_tridiagonal_qr(m)
+        }
+    }
+
+    pub fn jacobi(m : &DenseMatrix<f64>) -> SymmetricEigen {
+        SymmetricEigen::try_jacobi(m).unwrap_or_else(|e| panic!("symmetric eigen error : {}", e))
+    }
+
+    /// Cyclic Jacobi: sweeps over every off-diagonal pair, rotating each one
+    /// to zero, until the off-diagonal mass is negligible. Slow for large
+    /// matrices but very accurate for small eigenvalues.
+    pub fn try_jacobi(m : &DenseMatrix<f64>) -> Result<SymmetricEigen, MatrixError> {
+        let mut a = symmetric_rows(m)?;
+        let n = a.len();
+        let mut v = vec![vec![0.0; n]; n];
+        for (i, row) in v.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+        let total : f64 = a.iter().flat_map(|r| r.iter()).map(|x| x * x).sum();
+        let mut sweep = 0;
+        loop {
+            let mut off = 0.0;
+            for p in 0..n {
+                for q in (p + 1)..n {
+                    off += a[p][q] * a[p][q];
+                }
+            }
+            if off == 0.0 || off <= f64::EPSILON * f64::EPSILON * total {
+                break;
+            }
+            if sweep == JACOBI_MAX_SWEEPS {
+                return Err(MatrixError::NotConverged { iterations : sweep });
+            }
+            sweep += 1;
+            for p in 0..n {
+                for q in (p + 1)..n {
+                    if a[p][q] == 0.0 {
+                        continue;
+                    }
+                    let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                    let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                    let c = 1.0 / (t * t + 1.0).sqrt();
+                    let s = t * c;
+                    // A = Jᵀ * A * J, then V = V * J
+                    for k in 0..n {
+                        let (akp, akq) = (a[k][p], a[k][q]);
+                        a[k][p] = c * akp - s * akq;
+                        a[k][q] = s * akp + c * akq;
+                    }
+                    for k in 0..n {
+                        let (apk, aqk) = (a[p][k], a[q][k]);
+                        a[p][k] = c * apk - s * aqk;
+                        a[q][k] = s * apk + c * aqk;
+                    }
+                    for row in v.iter_mut() {
+                        let (vkp, vkq) = (row[p], row[q]);
+                        row[p] = c * vkp - s * vkq;
+                        row[q] = s * vkp + c * vkq;
+                    }
+                }
+            }
+        }
+        let values = (0..n).map(|i| a[i][i]).collect();
+        Ok(SymmetricEigen::sorted(values, v))
+    }
+
+    pub fn tridiagonal_qr(m : &DenseMatrix<f64>) -> SymmetricEigen {
+        SymmetricEigen::try_tridiagonal_qr(m).unwrap_or_else(|e| panic!("symmetric eigen error : {}", e))
+    }
+
+    /// Householder reduction to tridiagonal form followed by the implicit QL
+    /// algorithm with Wilkinson-style shifts (tred2 / tql2 from EISPACK).
+    pub fn try_tridiagonal_qr(m : &DenseMatrix<f64>) -> Result<SymmetricEigen, MatrixError> {
+        let mut v = symmetric_rows(m)?;
+        let n = v.len();
+        let mut d = vec![0.0; n];
+        let mut e = vec![0.0; n];
+        if n > 0 {
+            tridiagonalize(&mut v, &mut d, &mut e);
+            tridiagonal_ql(&mut v, &mut d, &mut e)?;
+        }
+        Ok(SymmetricEigen::sorted(d, v))
+    }
+
+    pub fn lanczos<A : LinearOperator + ?Sized>(a : &A, start : &Vector<f64>, steps : usize) -> SymmetricEigen {
+        SymmetricEigen::try_lanczos(a, start, steps).unwrap_or_else(|e| panic!("lanczos error : {}", e))
+    }
+
+    /// Ritz pairs of a symmetric operator from `steps` Lanczos iterations
+    /// started at `start`, with full reorthogonalization. Only `apply` is
+    /// used, so `a` can be matrix-free.
+    ///
+    /// The result has at most `steps` eigenvalues and `n x steps`
+    /// eigenvectors; the extremal ones converge first. It is shorter when
+    /// the Krylov space becomes invariant early (then the pairs are exact),
+    /// and empty for a zero `start`.
+    pub fn try_lanczos<A : LinearOperator + ?Sized>(a : &A, start : &Vector<f64>, steps : usize) -> Result<SymmetricEigen, MatrixError> {
+        let shape = a.get_shape();
+        check_square(shape)?;
+        let n = shape.0;
+        if start.length() != n {
+            return Err(MatrixError::DimensionMismatch { left : shape, right : (start.length(), 1) });
+        }
+        let dot = |x : &[f64], y : &[f64]| -> f64 { x.iter().zip(y.iter()).map(|(p, q)| p * q).sum() };
+        let start_norm = dot(start.as_slice(), start.as_slice()).sqrt();
+        let mut q : Vec<Vec<f64>> = Vec::new();
+        if start_norm != 0.0 {
+            q.push(start.as_slice().iter().map(|x| x / start_norm).collect());
+        }
+        let mut alpha : Vec<f64> = Vec::new();
+        let mut beta : Vec<f64> = Vec::new();
+        let mut scale : f64 = 0.0;
+        while alpha.len() < q.len() && alpha.len() < steps.min(n) {
+            let j = alpha.len();
+            let mut w = a.try_apply(&Vector::from_vec(q[j].clone()))?.into_vec();
+            let aj = dot(&w, &q[j]);
+            // the three-term recurrence, then Gram-Schmidt against every
+            // earlier vector to keep rounding from reintroducing them
+            for (k, qk) in q.iter().enumerate() {
+                let c = if k == j { aj } else if k + 1 == j { beta[k] } else { 0.0 };
+                for (wi, qi) in w.iter_mut().zip(qk.iter()) {
+                    *wi -= c * qi;
+                }
+            }
+            for qk in q.iter() {
+                let c = dot(&w, qk);
+                for (wi, qi) in w.iter_mut().zip(qk.iter()) {
+                    *wi -= c * qi;
+                }
+            }
+            alpha.push(aj);
+            let bj = dot(&w, &w).sqrt();
+            scale = scale.max(aj.abs() + bj + beta.last().cloned().unwrap_or(0.0));
+            if bj <= f64::EPSILON * scale {
+                break;
+            }
+            beta.push(bj);
+            q.push(w.iter().map(|x| x / bj).collect());
+        }
+        let m = alpha.len();
+        let mut t = DenseMatrix::new_with(m, m, 0.0);
+        for j in 0..m {
+            t.set(j, j, alpha[j]);
+            if j + 1 < m {
+                t.set(j, j + 1, beta[j]);
+                t.set(j + 1, j, beta[j]);
+            }
+        }
+        let small = SymmetricEigen::try_new(&t)?;
+        // Ritz vectors Q * S
+        let vectors = (0..n).map(|i| {
+            (0..m).map(|k| (0..m).map(|j| q[j][i] * small.vectors[j][k]).sum()).collect()
+        }).collect();
+        Ok(SymmetricEigen {
+            values : small.values,
+            vectors : vectors,
+        })
+    }
+
+    fn sorted(values : Vec<f64>, vectors : Vec<Vec<f64>>) -> SymmetricEigen {
+        let n = values.len();
+        let mut order : Vec<usize> = (0..n).collect();
+        order.sort_by(|&x, &y| values[x].partial_cmp(&values[y]).unwrap_or(std::cmp::Ordering::Equal));
+        SymmetricEigen {
+            values : order.iter().map(|&k| values[k]).collect(),
+            vectors : vectors.iter().map(|row| order.iter().map(|&k| row[k]).collect()).collect(),
+        }
+    }
+
+    /// Eigenvalues in ascending order.
+    pub fn get_eigenvalues(self : &Self) -> Vector<f64> {
+        Vector::from_vec(self.values.clone())
+    }
+
+    /// Orthonormal eigenvectors as columns, in the order of `get_eigenvalues`.
+    pub fn get_eigenvectors(self : &Self) -> DenseMatrix<f64> {
+        rows_to_dense(&self.vectors)
+    }
+}
+
+/// Reduces the symmetric matrix in `v` to tridiagonal form, leaving the
+/// diagonal in `d`, the subdiagonal in `e[1..]` and the accumulated
+/// orthogonal transformation in `v`.
+fn tridiagonalize(v : &mut [Vec<f64>], d : &mut [f64], e : &mut [f64]) {
+    let n = d.len();
+    d.copy_from_slice(&v[n - 1]);
+    for i in (1..n).rev() {
+        let scale : f64 = d[..i].iter().map(|x| x.abs()).sum();
+        let mut h = 0.0;
+        if scale == 0.0 {
+            e[i] = d[i - 1];
+            for j in 0..i {
+                d[j] = v[i - 1][j];
+                v[i][j] = 0.0;
+                v[j][i] = 0.0;
+            }
+        } else {
+            for k in 0..i {
+                d[k] /= scale;
+                h += d[k] * d[k];
+            }
+            let f = d[i - 1];
+            let g = if f > 0.0 { -h.sqrt() } else { h.sqrt() };
+            e[i] = scale * g;
+            h -= f * g;
+            d[i - 1] = f - g;
+            for j in 0..i {
+                e[j] = 0.0;
+            }
+            for j in 0..i {
+                let f = d[j];
+                v[j][i] = f;
+                let mut g = e[j] + v[j][j] * f;
+                for k in (j + 1)..i {
+                    g += v[k][j] * d[k];
+                    e[k] += v[k][j] * f;
+                }
+                e[j] = g;
+            }
+            let mut f = 0.0;
+            for j in 0..i {
+                e[j] /= h;
+                f += e[j] * d[j];
+            }
+            let hh = f / (h + h);
+            for j in 0..i {
+                e[j] -= hh * d[j];
+            }
+            for j in 0..i {
+                let (f, g) = (d[j], e[j]);
+                for k in j..i {
+                    v[k][j] -= f * e[k] + g * d[k];
+                }
+                d[j] = v[i - 1][j];
+                v[i][j] = 0.0;
+            }
+        }
+        d[i] = h;
+    }
+    for i in 0..(n - 1) {
+        v[n - 1][i] = v[i][i];
+        v[i][i] = 1.0;
+        let h = d[i + 1];
+        if h != 0.0 {
+            for k in 0..=i {
+                d[k] = v[k][i + 1] / h;
+            }
+            for j in 0..=i {
+                let g : f64 = (0..=i).map(|k| v[k][i + 1] * v[k][j]).sum();
+                for k in 0..=i {
+                    v[k][j] -= g * d[k];
+                }
+            }
+        }
+        for row in v.iter_mut().take(i + 1) {
+            row[i + 1] = 0.0;
+        }
+    }
+    for j in 0..n {
+        d[j] = v[n - 1][j];
+        v[n - 1][j] = 0.0;
+    }
+    v[n - 1][n - 1] = 1.0;
+    e[0] = 0.0;
+}
+
+/// Diagonalizes the tridiagonal matrix `(d, e)` produced by `tridiagonalize`,
+/// applying the rotations to `v`.
+fn tridiagonal_ql(v : &mut [Vec<f64>], d : &mut [f64], e : &mut [f64]) -> Result<(), MatrixError> {
+    let n = d.len();
+    for i in 1..n {
+        e[i - 1] = e[i];
+    }
+    e[n - 1] = 0.0;
+    let mut f = 0.0;
+    let mut tst1 : f64 = 0.0;
+    for l in 0..n {
+        tst1 = tst1.max(d[l].abs() + e[l].abs());
+        let mut m = l;
+        while m < n - 1 && e[m].abs() > f64::EPSILON * tst1 {
+            m += 1;
+        }
+        if m > l {
+            let mut iterations = 0;
+            loop {
+                if iterations == QL_MAX_ITERATIONS {
+                    return Err(MatrixError::NotConverged { iterations : iterations });
+                }
+                iterations += 1;
+                let g = d[l];
+                let mut p = (d[l + 1] - g) / (2.0 * e[l]);
+                let mut r = p.hypot(1.0);
+                if p < 0.0 {
+                    r = -r;
+                }
+                d[l] = e[l] / (p + r);
+                d[l + 1] = e[l] * (p + r);
+                let dl1 = d[l + 1];
+                let h = g - d[l];
+                for x in d.iter_mut().skip(l + 2) {
+                    *x -= h;
+                }
+                f += h;
+                p = d[m];
+                let (mut c, mut c2, mut c3) = (1.0, 1.0, 1.0);
+                let el1 = e[l + 1];
+                let (mut s, mut s2) = (0.0, 0.0);
+                for i in (l..m).rev() {
+                    c3 = c2;
+                    c2 = c;
+                    s2 = s;
+                    let g = c * e[i];
+                    let h = c * p;
+                    r = p.hypot(e[i]);
+                    e[i + 1] = s * r;
+                    s = e[i] / r;
+                    c = p / r;
+                    p = c * d[i] - s * g;
+                    d[i + 1] = h + s * (c * g + s * d[i]);
+                    for row in v.iter_mut() {
+                        let h = row[i + 1];
+                        row[i + 1] = s * row[i] + c * h;
+                        row[i] = c * row[i] - s * h;
+                    }
+                }
+                p = -s * s2 * c3 * el1 * e[l] / dl1;
+                e[l] = s * p;
+                d[l] = c * p;
+                if e[l].abs() <= f64::EPSILON * tst1 {
+                    break;
+                }
+            }
+        }
+        d[l] += f;
+        e[l] = 0.0;
+    }
+    Ok(())
+}
+
+fn rows_of(m : &DenseMatrix<f64>) -> Result<Vec<Vec<f64>>, MatrixError> {
+    check_square((m.get_row(), m.get_column()))?;
+    let n = m.get_row();
+    Ok(m.as_slice().chunks(n.max(1)).take(n).map(|r| r.to_vec()).collect())
+}
+
+fn rows_to_dense(rows : &[Vec<f64>]) -> DenseMatrix<f64> {
+    let cols = rows.first().map_or(0, |r| r.len());
+    DenseMatrix::from_vec(rows.len(), cols, rows.iter().flat_map(|r| r.iter().cloned()).collect())
+}
+
+/// Orthogonal reduction `A = Q * H * Qᵀ` of a square matrix to upper
+/// Hessenberg form (zero below the first subdiagonal).
+#[derive(Clone, Debug)]
+pub struct Hessenberg {
+    h : Vec<Vec<f64>>,
+    q : Vec<Vec<f64>>,
+}
+
+impl Hessenberg {
+    pub fn new(m : &DenseMatrix<f64>) -> Hessenberg {
+        Hessenberg::try_new(m).unwrap_or_else(|e| panic!("hessenberg error : {}", e))
+    }
+
+    pub fn try_new(m : &DenseMatrix<f64>) -> Result<Hessenberg, MatrixError> {
+        let mut h = rows_of(m)?;
+        let q = reduce_to_hessenberg(&mut h);
+        Ok(Hessenberg {
+            h : h,
+            q : q,
+        })
+    }
+
+    pub fn get_h(self : &Self) -> DenseMatrix<f64> {
+        rows_to_dense(&self.h)
+    }
+
+    pub fn get_q(self : &Self) -> DenseMatrix<f64> {
+        rows_to_dense(&self.q)
+    }
+}
+
+/// Householder reduction of `h` to Hessenberg form in place (orthes from
+/// EISPACK); returns the accumulated orthogonal transformation.
+fn reduce_to_hessenberg(h : &mut [Vec<f64>]) -> Vec<Vec<f64>> {
+    let n = h.len();
+    let mut ort = vec![0.0; n];
+    for m in 1..n.saturating_sub(1) {
+        let scale : f64 = (m..n).map(|i| h[i][m - 1].abs()).sum();
+        if scale == 0.0 {
+            continue;
+        }
+        let mut hh = 0.0;
+        for i in (m..n).rev() {
+            ort[i] = h[i][m - 1] / scale;
+            hh += ort[i] * ort[i];
+        }
+        let g = if ort[m] > 0.0 { -hh.sqrt() } else { hh.sqrt() };
+        hh -= ort[m] * g;
+        ort[m] -= g;
+        // H = (I - u * uᵀ / hh) * H * (I - u * uᵀ / hh)
+        for j in m..n {
+            let f = (m..n).rev().map(|i| ort[i] * h[i][j]).sum::<f64>() / hh;
+            for i in m..n {
+                h[i][j] -= f * ort[i];
+            }
+        }
+        for row in h.iter_mut() {
+            let f = (m..n).rev().map(|j| ort[j] * row[j]).sum::<f64>() / hh;
+            for j in m..n {
+                row[j] -= f * ort[j];
+            }
+        }
+        ort[m] *= scale;
+        h[m][m - 1] = scale * g;
+    }
+    let mut q = vec![vec![0.0; n]; n];
+    for (i, row) in q.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+    for m in (1..n.saturating_sub(1)).rev() {
+        if h[m][m - 1] == 0.0 {
+            continue;
+        }
+        for i in (m + 1)..n {
+            ort[i] = h[i][m - 1];
+        }
+        for j in m..n {
+            let g : f64 = (m..n).map(|i| ort[i] * q[i][j]).sum();
+            // double division avoids possible underflow
+            let g = (g / ort[m]) / h[m][m - 1];
+            for i in m..n {
+                q[i][j] += g * ort[i];
+            }
+        }
+    }
+    // the reflectors were left below the subdiagonal
+    for i in 0..n {
+        for j in 0..i.saturating_sub(1) {
+            h[i][j] = 0.0;
+        }
+    }
+    q
+}
+
+/// Real Schur form `A = Z * T * Zᵀ`: `Z` is orthogonal and `T` is upper
+/// quasi-triangular, with a 2 x 2 block on the diagonal for every pair of
+/// complex conjugate eigenvalues.
+#[derive(Clone, Debug)]
+pub struct RealSchur {
+    t : DenseMatrix<f64>,
+    z : DenseMatrix<f64>,
+}
+
+impl RealSchur {
+    pub fn get_t(self : &Self) -> &DenseMatrix<f64> {
+        &self.t
+    }
+
+    pub fn get_z(self : &Self) -> &DenseMatrix<f64> {
+        &self.z
+    }
+}
+
+/// Eigenvalues of a general real square matrix, by Hessenberg reduction and
+/// Francis double-shift QR iteration (hqr2 from EISPACK, without the
+/// eigenvector back-substitution).
+#[derive(Clone, Debug)]
+pub struct Eigen {
+    values : Vec<Complex<f64>>,
+    schur : Option<RealSchur>,
+}
+
+impl Eigen {
+    pub fn new(m : &DenseMatrix<f64>) -> Eigen {
+        Eigen::try_new(m).unwrap_or_else(|e| panic!("eigen error : {}", e))
+    }
+
+    pub fn try_new(m : &DenseMatrix<f64>) -> Result<Eigen, MatrixError> {
+        Eigen::compute(m, false)
+    }
+
+    /// Also keeps the real Schur form, which costs the accumulation of every
+    /// transformation.
+    pub fn new_with_schur(m : &DenseMatrix<f64>) -> Eigen {
+        Eigen::try_new_with_schur(m).unwrap_or_else(|e| panic!("eigen error : {}", e))
+    }
+
+    pub fn try_new_with_schur(m : &DenseMatrix<f64>) -> Result<Eigen, MatrixError> {
+        Eigen::compute(m, true)
+    }
+
+    fn compute(m : &DenseMatrix<f64>, want_schur : bool) -> Result<Eigen, MatrixError> {
+        let mut h = rows_of(m)?;
+        let q = reduce_to_hessenberg(&mut h);
+        let mut z = if want_schur { Some(q) } else { None };
+        let values = francis_qr(&mut h, z.as_mut())?;
+        Ok(Eigen {
+            values : values,
+            schur : z.map(|z| RealSchur {
+                t : rows_to_dense(&h),
+                z : rows_to_dense(&z),
+            }),
+        })
+    }
+
+    /// The eigenvalues in the order they appear on the diagonal of `T`; a
+    /// conjugate pair is adjacent, positive imaginary part first.
+    pub fn get_eigenvalues(self : &Self) -> &[Complex<f64>] {
+        &self.values
+    }
+
+    /// `Some` only when built by `new_with_schur`.
+    pub fn get_schur(self : &Self) -> Option<&RealSchur> {
+        self.schur.as_ref()
+    }
+}
+
+/// Reduces the Hessenberg matrix `h` to real Schur form in place, applying
+/// every transformation to `z` as well when given.
+fn francis_qr(h : &mut [Vec<f64>], mut z : Option<&mut Vec<Vec<f64>>>) -> Result<Vec<Complex<f64>>, MatrixError> {
+    let nn = h.len();
+    let mut d = vec![0.0; nn];
+    let mut e = vec![0.0; nn];
+    let eps = f64::EPSILON;
+    let mut exshift = 0.0;
+    let mut norm = 0.0;
+    for i in 0..nn {
+        for j in i.saturating_sub(1)..nn {
+            norm += h[i][j].abs();
+        }
+    }
+    if norm == 0.0 {
+        // nothing would ever deflate against a zero norm
+        return Ok(vec![Complex::new(0.0, 0.0); nn]);
+    }
+    let mut iter = 0;
+    let mut active = nn as isize - 1;
+    while active >= 0 {
+        let n = active as usize;
+        // look for a single small subdiagonal element
+        let mut l = n;
+        while l > 0 {
+            let mut s = h[l - 1][l - 1].abs() + h[l][l].abs();
+            if s == 0.0 {
+                s = norm;
+            }
+            if h[l][l - 1].abs() < eps * s {
+                h[l][l - 1] = 0.0;
+                break;
+            }
+            l -= 1;
+        }
+        if l == n {
+            // one root
+            h[n][n] += exshift;
+            d[n] = h[n][n];
+            e[n] = 0.0;
+            active -= 1;
+            iter = 0;
+        } else if l == n - 1 {
+            // two roots
+            let w = h[n][n - 1] * h[n - 1][n];
+            let p = (h[n - 1][n - 1] - h[n][n]) / 2.0;
+            let q = p * p + w;
+            let zz = q.abs().sqrt();
+            h[n][n] += exshift;
+            h[n - 1][n - 1] += exshift;
+            let x = h[n][n];
+            if q >= 0.0 {
+                // real pair: rotate the block to upper triangular
+                let zz = if p >= 0.0 { p + zz } else { p - zz };
+                d[n - 1] = x + zz;
+                d[n] = if zz != 0.0 { x - w / zz } else { d[n - 1] };
+                e[n - 1] = 0.0;
+                e[n] = 0.0;
+                let x = h[n][n - 1];
+                let s = x.abs() + zz.abs();
+                let (p, q) = (x / s, zz / s);
+                let r = (p * p + q * q).sqrt();
+                let (p, q) = (p / r, q / r);
+                for j in (n - 1)..nn {
+                    let t = h[n - 1][j];
+                    h[n - 1][j] = q * t + p * h[n][j];
+                    h[n][j] = q * h[n][j] - p * t;
+                }
+                for row in h.iter_mut().take(n + 1) {
+                    let t = row[n - 1];
+                    row[n - 1] = q * t + p * row[n];
+                    row[n] = q * row[n] - p * t;
+                }
+                if let Some(z) = z.as_mut() {
+                    for row in z.iter_mut() {
+                        let t = row[n - 1];
+                        row[n - 1] = q * t + p * row[n];
+                        row[n] = q * row[n] - p * t;
+                    }
+                }
+                h[n][n - 1] = 0.0;
+            } else {
+                d[n - 1] = x + p;
+                d[n] = x + p;
+                e[n - 1] = zz;
+                e[n] = -zz;
+            }
+            active -= 2;
+            iter = 0;
+        } else {
+            if iter == HQR_MAX_ITERATIONS {
+                return Err(MatrixError::NotConverged { iterations : iter });
+            }
+            // form the shift
+            let mut x = h[n][n];
+            let mut y = h[n - 1][n - 1];
+            let mut w = h[n][n - 1] * h[n - 1][n];
+            if iter == 10 {
+                // Wilkinson's ad hoc exceptional shift
+                exshift += x;
+                for i in 0..=n {
+                    h[i][i] -= x;
+                }
+                let s = h[n][n - 1].abs() + h[n - 1][n - 2].abs();
+                x = 0.75 * s;
+                y = x;
+                w = -0.4375 * s * s;
+            }
+            if iter == 30 {
+                // MATLAB's exceptional shift
+                let s = (y - x) / 2.0;
+                let s = s * s + w;
+                if s > 0.0 {
+                    let s = if y < x { -s.sqrt() } else { s.sqrt() };
+                    let s = x - w / ((y - x) / 2.0 + s);
+                    for i in 0..=n {
+                        h[i][i] -= s;
+                    }
+                    exshift += s;
+                    x = 0.964;
+                    y = x;
+                    w = x;
+                }
+            }
+            iter += 1;
+            // look for two consecutive small subdiagonal elements
+            let mut m = n - 2;
+            let (mut p, mut q, mut r);
+            loop {
+                let zz = h[m][m];
+                let rr = x - zz;
+                let ss = y - zz;
+                p = (rr * ss - w) / h[m + 1][m] + h[m][m + 1];
+                q = h[m + 1][m + 1] - zz - rr - ss;
+                r = h[m + 2][m + 1];
+                let s = p.abs() + q.abs() + r.abs();
+                p /= s;
+                q /= s;
+                r /= s;
+                if m == l {
+                    break;
+                }
+                if h[m][m - 1].abs() * (q.abs() + r.abs())
+                    < eps * (p.abs() * (h[m - 1][m - 1].abs() + zz.abs() + h[m + 1][m + 1].abs())) {
+                    break;
+                }
+                m -= 1;
+            }
+            for i in (m + 2)..=n {
+                h[i][i - 2] = 0.0;
+                if i > m + 2 {
+                    h[i][i - 3] = 0.0;
+                }
+            }
+            // double QR step on rows l..=n and columns m..=n
+            for k in m..n {
+                let notlast = k != n - 1;
+                if k != m {
+                    p = h[k][k - 1];
+                    q = h[k + 1][k - 1];
+                    r = if notlast { h[k + 2][k - 1] } else { 0.0 };
+                    x = p.abs() + q.abs() + r.abs();
+                    if x == 0.0 {
+                        continue;
+                    }
+                    p /= x;
+                    q /= x;
+                    r /= x;
+                }
+                let mut s = (p * p + q * q + r * r).sqrt();
+                if p < 0.0 {
+                    s = -s;
+                }
+                if s == 0.0 {
+                    continue;
+                }
+                if k != m {
+                    h[k][k - 1] = -s * x;
+                } else if l != m {
+                    h[k][k - 1] = -h[k][k - 1];
+                }
+                p += s;
+                x = p / s;
+                y = q / s;
+                let zz = r / s;
+                q /= p;
+                r /= p;
+                for j in k..nn {
+                    let mut t = h[k][j] + q * h[k + 1][j];
+                    if notlast {
+                        t += r * h[k + 2][j];
+                        h[k + 2][j] -= t * zz;
+                    }
+                    h[k][j] -= t * x;
+                    h[k + 1][j] -= t * y;
+                }
+                for row in h.iter_mut().take(n.min(k + 3) + 1) {
+                    let mut t = x * row[k] + y * row[k + 1];
+                    if notlast {
+                        t += zz * row[k + 2];
+                        row[k + 2] -= t * r;
+                    }
+                    row[k] -= t;
+                    row[k + 1] -= t * q;
+                }
+                if let Some(z) = z.as_mut() {
+                    for row in z.iter_mut() {
+                        let mut t = x * row[k] + y * row[k + 1];
+                        if notlast {
+                            t += zz * row[k + 2];
+                            row[k + 2] -= t * r;
+                        }
+                        row[k] -= t;
+                        row[k + 1] -= t * q;
+                    }
+                }
+            }
+        }
+    }
+    // the bulge chasing leaves roundoff below the subdiagonal
+    for i in 2..nn {
+        for j in 0..(i - 1) {
+            h[i][j] = 0.0;
+        }
+    }
+    Ok(d.into_iter().zip(e).map(|(re, im)| Complex::new(re, im)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix;
+
+    fn check_decomposition(a : &DenseMatrix<f64>, eig : &SymmetricEigen) {
+        let n = a.get_row();
+        let values = eig.get_eigenvalues();
+        let v = eig.get_eigenvectors();
+        for k in 1..n {
+            assert!(values[k - 1] <= values[k]);
+        }
+        let vtv = &v.transpose() * &v;
+        let av = a * &v;
+        for i in 0..n {
+            for j in 0..n {
+                let id = if i == j { 1.0 } else { 0.0 };
+                assert!((vtv.get(i, j).unwrap() - id).abs() < 1e-10);
+                let lv = v.get(i, j).unwrap() * values[j];
+                assert!((av.get(i, j).unwrap() - lv).abs() < 1e-9, "{} != {}", av.get(i, j).unwrap(), lv);
+            }
+        }
+    }
+
+    fn sample(n : usize) -> DenseMatrix<f64> {
+        let mut a = DenseMatrix::new_with(n, n, 0.0);
+        for i in 0..n {
+            for j in 0..n {
+                a.set(i, j, 1.0 / (1.0 + i as f64 + j as f64) + if i == j { (i % 5) as f64 } else { 0.0 });
+            }
+        }
+        a
+    }
+
+    #[test]
+    fn symmetric_eigen_small() {
+        let a = matrix![DenseMatrix<f64> => (2.0, 1.0, 0.0);(1.0, 2.0, 1.0);(0.0, 1.0, 2.0)];
+        let expected = [2.0 - 2.0f64.sqrt(), 2.0, 2.0 + 2.0f64.sqrt()];
+        for eig in [SymmetricEigen::jacobi(&a), SymmetricEigen::tridiagonal_qr(&a)].iter() {
+            let values = eig.get_eigenvalues();
+            for k in 0..3 {
+                assert!((values[k] - expected[k]).abs() < 1e-12);
+            }
+            check_decomposition(&a, eig);
+        }
+    }
+
+    #[test]
+    fn symmetric_eigen_methods_agree() {
+        let a = sample(24);
+        let ql = SymmetricEigen::new(&a);
+        let jacobi = SymmetricEigen::jacobi(&a);
+        check_decomposition(&a, &ql);
+        check_decomposition(&a, &jacobi);
+        for k in 0..24 {
+            assert!((ql.get_eigenvalues()[k] - jacobi.get_eigenvalues()[k]).abs() < 1e-10);
+        }
+        let diag = matrix![DenseMatrix<f64> => (3.0, 0.0);(0.0, -1.0)];
+        assert_eq!(SymmetricEigen::new(&diag).get_eigenvalues(), crate::vector![-1.0, 3.0]);
+        let rect = DenseMatrix::new_with(2, 3, 0.0);
+        assert_eq!(SymmetricEigen::try_new(&rect).unwrap_err(), MatrixError::NotSquare { shape : (2, 3) });
+    }
+
+    fn general_sample() -> DenseMatrix<f64> {
+        let mut a = DenseMatrix::new_with(6, 6, 0.0);
+        for i in 0..6 {
+            for j in 0..6 {
+                a.set(i, j, ((i * 7 + j * 3) % 11) as f64 - 5.0);
+            }
+        }
+        a
+    }
+
+    fn assert_close(a : &DenseMatrix<f64>, b : &DenseMatrix<f64>) {
+        for (x, y) in a.as_slice().iter().zip(b.as_slice().iter()) {
+            assert!((x - y).abs() < 1e-9, "{} != {}", x, y);
+        }
+    }
+
+    #[test]
+    fn hessenberg_reduction() {
+        let a = general_sample();
+        let hess = Hessenberg::new(&a);
+        let (h, q) = (hess.get_h(), hess.get_q());
+        for i in 2..6 {
+            for j in 0..(i - 1) {
+                assert_eq!(*h.get(i, j).unwrap(), 0.0);
+            }
+        }
+        assert_close(&(&(&q * &h) * &q.transpose()), &a);
+        assert_close(&(&q.transpose() * &q), &DenseMatrix::get_identity_matrix(6));
+    }
+
+    #[test]
+    fn eigen_complex_pairs() {
+        // companion matrix of (x - 1)(x² + 1)
+        let a = matrix![DenseMatrix<f64> => (1.0, -1.0, 1.0);(1.0, 0.0, 0.0);(0.0, 1.0, 0.0)];
+        let mut values = Eigen::new(&a).get_eigenvalues().to_vec();
+        values.sort_by(|x, y| x.get_im().partial_cmp(&y.get_im()).unwrap());
+        let expected = [(0.0, -1.0), (1.0, 0.0), (0.0, 1.0)];
+        for (v, e) in values.iter().zip(expected.iter()) {
+            assert!((v.get_re() - e.0).abs() < 1e-12 && (v.get_im() - e.1).abs() < 1e-12, "{}", v);
+        }
+        let rotation = matrix![DenseMatrix<f64> => (0.0, -1.0);(1.0, 0.0)];
+        let eig = Eigen::new(&rotation);
+        assert_eq!(eig.get_eigenvalues(), &[Complex::new(0.0, 1.0), Complex::new(0.0, -1.0)]);
+        assert!(eig.get_schur().is_none());
+    }
+
+    #[test]
+    fn eigen_zero_matrix() {
+        let zero = DenseMatrix::new_with(3, 3, 0.0);
+        assert_eq!(Eigen::new(&zero).get_eigenvalues(), &[Complex::new(0.0, 0.0); 3]);
+        let eig = Eigen::new_with_schur(&zero);
+        let schur = eig.get_schur().unwrap();
+        assert_close(schur.get_t(), &zero);
+        assert_close(schur.get_z(), &DenseMatrix::get_identity_matrix(3));
+    }
+
+    #[test]
+    fn eigen_real_schur() {
+        let a = general_sample();
+        let eig = Eigen::new_with_schur(&a);
+        let schur = eig.get_schur().unwrap();
+        let (t, z) = (schur.get_t(), schur.get_z());
+        assert_close(&(&(z * t) * &z.transpose()), &a);
+        assert_close(&(&z.transpose() * z), &DenseMatrix::get_identity_matrix(6));
+        for i in 2..6 {
+            for j in 0..(i - 1) {
+                assert_eq!(*t.get(i, j).unwrap(), 0.0);
+            }
+        }
+        for i in 2..6 {
+            assert!(*t.get(i, i - 1).unwrap() == 0.0 || *t.get(i - 1, i - 2).unwrap() == 0.0);
+        }
+        let values = eig.get_eigenvalues();
+        let trace : f64 = values.iter().map(|v| v.get_re()).sum();
+        assert!((trace - (0..6).map(|i| a.get(i, i).unwrap()).sum::<f64>()).abs() < 1e-9);
+        assert!(values.iter().map(|v| v.get_im()).sum::<f64>().abs() < 1e-12);
+        let plain = Eigen::new(&a);
+        for (x, y) in plain.get_eigenvalues().iter().zip(values.iter()) {
+            assert!((x.get_re() - y.get_re()).abs() < 1e-12 && (x.get_im() - y.get_im()).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn lanczos_matrix_free() {
+        use crate::linear_operator::FnOperator;
+        // the 1-D Laplacian, whose eigenvalues are 2 - 2 cos(k π / (n + 1))
+        let n = 30;
+        let laplacian = FnOperator::new(n, n, move |x : &Vector<f64>| {
+            Vector::from_vec((0..n).map(|i| {
+                let left = if i > 0 { x[i - 1] } else { 0.0 };
+                let right = if i + 1 < n { x[i + 1] } else { 0.0 };
+                2.0 * x[i] - left - right
+            }).collect())
+        });
+        let start = Vector::from_vec((0..n).map(|i| 1.0 + (i % 3) as f64).collect());
+        let eig = SymmetricEigen::lanczos(&laplacian, &start, n);
+        let values = eig.get_eigenvalues();
+        assert_eq!(values.length(), n);
+        for k in 0..n {
+            let exact = 2.0 - 2.0 * (((k + 1) as f64) * std::f64::consts::PI / (n + 1) as f64).cos();
+            assert!((values[k] - exact).abs() < 1e-10, "{} != {}", values[k], exact);
+        }
+        // a well separated top eigenvalue converges in a few steps
+        let mut a = DenseMatrix::new_with(40, 40, 0.0);
+        for i in 0..40 {
+            a.set(i, i, 1.0 + i as f64 / 40.0);
+        }
+        a.set(39, 39, 10.0);
+        let eig = SymmetricEigen::lanczos(&a, &Vector::new_with(40, 1.0), 8);
+        assert_eq!(eig.get_eigenvectors().get_column(), 8);
+        assert!((eig.get_eigenvalues()[7] - 10.0).abs() < 1e-10);
+        assert!((eig.get_eigenvectors().get(39, 7).unwrap().abs() - 1.0).abs() < 1e-8);
+        // an eigenvector as the start spans an invariant space at once
+        let mut e = Vector::new_with(40, 0.0);
+        e.set(3, 1.0);
+        assert_eq!(SymmetricEigen::lanczos(&a, &e, 8).get_eigenvalues().length(), 1);
+        let long = FnOperator::new(2, 2, |x : &Vector<f64>| Vector::from_vec(vec![x[0], x[1], 0.0]));
+        assert_eq!(SymmetricEigen::try_lanczos(&long, &Vector::from_vec(vec![1.0, 0.0]), 2).unwrap_err(), MatrixError::DimensionMismatch { left : (2, 1), right : (3, 1) });
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt::{Formatter, Display};
+
+/// Why a `try_*` operation failed. Where a panicking operation has a
+/// `try_*` twin, the twin returns one of these instead of panicking; plain
+/// element access (`get`, the `Index` operators) panics on a bad index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatrixError {
+    /// The two operands have incompatible shapes, given as `[row, col]`
+    /// (a vector of length n is reported as `[n, 1]`).
+    DimensionMismatch { left : (usize, usize), right : (usize, usize) },
+    /// `index` lies outside a matrix of the given `shape`.
+    IndexOutOfBounds { index : (usize, usize), shape : (usize, usize) },
+    /// The matrix has no inverse.
+    Singular,
+    /// The operation needs a square matrix.
+    NotSquare { shape : (usize, usize) },
+    /// A Cholesky factorization met a non-positive pivot at this index.
+    NotPositiveDefinite { pivot : usize },
+    /// An iterative algorithm did not converge within this many iterations.
+    NotConverged { iterations : usize },
+    /// An iterative method hit a division by zero (or, for CG, a direction of
+    /// non-positive curvature) at this iteration.
+    Breakdown { iteration : usize },
+    /// Raw storage arrays handed to a constructor are inconsistent.
+    InvalidStorage { reason : &'static str },
+    /// A scalar parameter lies outside the range the method accepts.
+    InvalidArgument { reason : &'static str },
+}
+
+impl Display for MatrixError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatrixError::DimensionMismatch { left, right } =>
+                write!(f, "dimension mismatch : [{}, {}] vs [{}, {}]", left.0, left.1, right.0, right.1),
+            MatrixError::IndexOutOfBounds { index, shape } =>
+                write!(f, "index [{}, {}] out of range for [{}, {}] matrix", index.0, index.1, shape.0, shape.1),
+            MatrixError::Singular =>
+                write!(f, "matrix is singular"),
+            MatrixError::NotSquare { shape } =>
+                write!(f, "matrix need row == col, got [{}, {}]", shape.0, shape.1),
+            MatrixError::NotPositiveDefinite { pivot } =>
+                write!(f, "matrix is not positive definite, failed at pivot {}", pivot),
+            MatrixError::NotConverged { iterations } =>
+                write!(f, "no convergence after {} iterations", iterations),
+            MatrixError::Breakdown { iteration } =>
+                write!(f, "iterative method broke down at iteration {}", iteration),
+            MatrixError::InvalidStorage { reason } =>
+                write!(f, "invalid matrix storage : {}", reason),
+            MatrixError::InvalidArgument { reason } =>
+                write!(f, "invalid argument : {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for MatrixError {}
+
+pub(crate) fn check_index(row : usize, col : usize, shape : (usize, usize)) -> Result<(), MatrixError> {
+    if row >= shape.0 || col >= shape.1 {
+        return Err(MatrixError::IndexOutOfBounds { index : (row, col), shape : shape });
+    }
+    Ok(())
+}
+
+pub(crate) fn check_square(shape : (usize, usize)) -> Result<(), MatrixError> {
+    if shape.0 != shape.1 {
+        return Err(MatrixError::NotSquare { shape : shape });
+    }
+    Ok(())
+}
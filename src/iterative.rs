@@ -0,0 +1,458 @@
+use crate::linear_operator::LinearOperator;
+use crate::vector::Vector;
+use crate::preconditioner::Preconditioner;
+use crate::error::*;
+
+fn dot(x : &[f64], y : &[f64]) -> f64 {
+    x.iter().zip(y.iter()).map(|(a, b)| a * b).sum()
+}
+
+fn norm(x : &[f64]) -> f64 {
+    dot(x, x).sqrt()
+}
+
+/// `y += alpha * x`
+fn axpy(alpha : f64, x : &[f64], y : &mut [f64]) {
+    for (yi, xi) in y.iter_mut().zip(x.iter()) {
+        *yi += alpha * xi;
+    }
+}
+
+fn check_system<A : LinearOperator + ?Sized>(a : &A, b : &Vector<f64>) -> Result<(), MatrixError> {
+    let shape = a.get_shape();
+    check_square(shape)?;
+    if b.length() != shape.0 {
+        return Err(MatrixError::DimensionMismatch { left : shape, right : (b.length(), 1) });
+    }
+    Ok(())
+}
+
+/// The outcome of an iterative solve that did not break down.
+///
+/// Hitting the iteration cap is not an error: `is_converged` is then false
+/// and `get_solution` holds the last iterate.
+#[derive(Clone, Debug)]
+pub struct IterativeSolution {
+    solution : Vector<f64>,
+    iterations : usize,
+    residual_history : Vec<f64>,
+    converged : bool,
+}
+
+impl IterativeSolution {
+    pub fn get_solution(self : &Self) -> &Vector<f64> {
+        &self.solution
+    }
+
+    pub fn into_solution(self : Self) -> Vector<f64> {
+        self.solution
+    }
+
+    pub fn get_iterations(self : &Self) -> usize {
+        self.iterations
+    }
+
+    /// Relative residuals `|b - A * x_k|₂ / |b|₂`, starting with `x_0 = 0`.
+    pub fn get_residual_history(self : &Self) -> &[f64] {
+        &self.residual_history
+    }
+
+    pub fn is_converged(self : &Self) -> bool {
+        self.converged
+    }
+}
+
+pub fn conjugate_gradient<A : LinearOperator + ?Sized, P : Preconditioner>(a : &A, b : &Vector<f64>, preconditioner : &P, tol : f64, max_iterations : usize) -> IterativeSolution {
+    try_conjugate_gradient(a, b, preconditioner, tol, max_iterations).unwrap_or_else(|e| panic!("conjugate gradient error : {}", e))
+}
+
+/// Preconditioned Conjugate Gradient for a symmetric positive definite `A`
+/// (and an SPD preconditioner), starting from `x = 0` and stopping once the
+/// relative residual is at most `tol`.
+///
+/// Fails with `Breakdown` if a search direction has non-positive curvature
+/// `pᵀ * A * p`, which means `A` is not positive definite.
+pub fn try_conjugate_gradient<A : LinearOperator + ?Sized, P : Preconditioner>(a : &A, b : &Vector<f64>, preconditioner : &P, tol : f64, max_iterations : usize) -> Result<IterativeSolution, MatrixError> {
+    check_system(a, b)?;
+    let n = b.length();
+    let mut x = vec![0.0; n];
+    let mut r = b.as_slice().to_vec();
+    let b_norm = norm(&r);
+    let mut history = vec![if b_norm == 0.0 { 0.0 } else { 1.0 }];
+    let mut converged = b_norm == 0.0;
+    let mut iterations = 0;
+    if !converged {
+        let mut z = preconditioner.apply(&Vector::from_vec(r.clone())).into_vec();
+        let mut p = z.clone();
+        let mut rz = dot(&r, &z);
+        while iterations < max_iterations {
+            iterations += 1;
+            let ap = a.try_apply(&Vector::from_vec(p.clone()))?.into_vec();
+            let curvature = dot(&p, &ap);
+            if curvature <= 0.0 || curvature.is_nan() {
+                return Err(MatrixError::Breakdown { iteration : iterations });
+            }
+            let alpha = rz / curvature;
+            axpy(alpha, &p, &mut x);
+            axpy(-alpha, &ap, &mut r);
+            let relative = norm(&r) / b_norm;
+            history.push(relative);
+            if relative <= tol {
+                converged = true;
+                break;
+            }
+            z = preconditioner.apply(&Vector::from_vec(r.clone())).into_vec();
+            let rz_next = dot(&r, &z);
+            let beta = rz_next / rz;
+            rz = rz_next;
+            for (pi, zi) in p.iter_mut().zip(z.iter()) {
+                *pi = zi + beta * *pi;
+            }
+        }
+    }
+    Ok(IterativeSolution {
+        solution : Vector::from_vec(x),
+        iterations : iterations,
+        residual_history : history,
+        converged : converged,
+    })
+}
+
+pub fn gmres<A : LinearOperator + ?Sized, P : Preconditioner>(a : &A, b : &Vector<f64>, preconditioner : &P, restart : usize, tol : f64, max_iterations : usize) -> IterativeSolution {
+    try_gmres(a, b, preconditioner, restart, tol, max_iterations).unwrap_or_else(|e| panic!("gmres error : {}", e))
+}
+
+/// Restarted GMRES(m) with right preconditioning, for general square `A`.
+///
+/// Each cycle builds an Arnoldi basis of at most `restart` vectors and
+/// minimizes the residual over it, using Givens rotations so that the
+/// residual norm is known at every step without forming `x`. Since the
+/// preconditioner is applied on the right, the residuals in the history are
+/// those of the original system. Every inner step counts as one iteration.
+/// A `restart` of zero fails with `InvalidArgument`.
+pub fn try_gmres<A : LinearOperator + ?Sized, P : Preconditioner>(a : &A, b : &Vector<f64>, preconditioner : &P, restart : usize, tol : f64, max_iterations : usize) -> Result<IterativeSolution, MatrixError> {
+    check_system(a, b)?;
+    if restart == 0 {
+        return Err(MatrixError::InvalidArgument { reason : "restart must be positive" });
+    }
+    let n = b.length();
+    let m = restart;
+    let mut x = vec![0.0; n];
+    let b_norm = norm(b.as_slice());
+    let mut history = vec![if b_norm == 0.0 { 0.0 } else { 1.0 }];
+    let mut converged = b_norm == 0.0;
+    let mut iterations = 0;
+    while !converged && iterations < max_iterations {
+        let ax = a.try_apply(&Vector::from_vec(x.clone()))?.into_vec();
+        let r : Vec<f64> = b.as_slice().iter().zip(ax.iter()).map(|(bi, axi)| bi - axi).collect();
+        let beta = norm(&r);
+        if beta / b_norm <= tol {
+            converged = true;
+            break;
+        }
+        let mut v : Vec<Vec<f64>> = vec![r.iter().map(|ri| ri / beta).collect()];
+        let mut z : Vec<Vec<f64>> = Vec::with_capacity(m);
+        // h[j] is column j of the Hessenberg matrix, rotated to triangular
+        let mut h : Vec<Vec<f64>> = Vec::with_capacity(m);
+        let mut cs : Vec<f64> = Vec::with_capacity(m);
+        let mut sn : Vec<f64> = Vec::with_capacity(m);
+        let mut g = vec![beta];
+        while h.len() < m && iterations < max_iterations {
+            iterations += 1;
+            let j = h.len();
+            let zj = preconditioner.apply(&Vector::from_vec(v[j].clone())).into_vec();
+            let mut w = a.try_apply(&Vector::from_vec(zj.clone()))?.into_vec();
+            z.push(zj);
+            let mut column = Vec::with_capacity(j + 2);
+            for vi in v.iter() {
+                let hij = dot(&w, vi);
+                axpy(-hij, vi, &mut w);
+                column.push(hij);
+            }
+            let w_norm = norm(&w);
+            column.push(w_norm);
+            for i in 0..j {
+                let (p, q) = (column[i], column[i + 1]);
+                column[i] = cs[i] * p + sn[i] * q;
+                column[i + 1] = -sn[i] * p + cs[i] * q;
+            }
+            let d = column[j].hypot(column[j + 1]);
+            if d == 0.0 {
+                return Err(MatrixError::Breakdown { iteration : iterations });
+            }
+            cs.push(column[j] / d);
+            sn.push(column[j + 1] / d);
+            column[j] = d;
+            column.pop();
+            h.push(column);
+            g.push(-sn[j] * g[j]);
+            g[j] *= cs[j];
+            let relative = g[j + 1].abs() / b_norm;
+            history.push(relative);
+            if relative <= tol {
+                converged = true;
+                break;
+            }
+            if w_norm == 0.0 {
+                // the Krylov space is invariant, so the cycle is exact
+                break;
+            }
+            v.push(w.iter().map(|wi| wi / w_norm).collect());
+        }
+        // y = H⁻¹ * g on the triangle, then x += Z * y
+        let k = h.len();
+        let mut y = vec![0.0; k];
+        for i in (0..k).rev() {
+            let mut s = g[i];
+            for l in (i + 1)..k {
+                s -= h[l][i] * y[l];
+            }
+            y[i] = s / h[i][i];
+        }
+        for (yi, zi) in y.iter().zip(z.iter()) {
+            axpy(*yi, zi, &mut x);
+        }
+    }
+    Ok(IterativeSolution {
+        solution : Vector::from_vec(x),
+        iterations : iterations,
+        residual_history : history,
+        converged : converged,
+    })
+}
+
+pub fn bicgstab<A : LinearOperator + ?Sized, P : Preconditioner>(a : &A, b : &Vector<f64>, preconditioner : &P, tol : f64, max_iterations : usize) -> IterativeSolution {
+    try_bicgstab(a, b, preconditioner, tol, max_iterations).unwrap_or_else(|e| panic!("bicgstab error : {}", e))
+}
+
+/// BiCGSTAB with right preconditioning, for general square `A`. Needs only
+/// short recurrences, so memory stays constant, but the method can break
+/// down when an inner product vanishes; that is reported as `Breakdown`.
+pub fn try_bicgstab<A : LinearOperator + ?Sized, P : Preconditioner>(a : &A, b : &Vector<f64>, preconditioner : &P, tol : f64, max_iterations : usize) -> Result<IterativeSolution, MatrixError> {
+    check_system(a, b)?;
+    let n = b.length();
+    let mut x = vec![0.0; n];
+    let mut r = b.as_slice().to_vec();
+    let r_hat = r.clone();
+    let b_norm = norm(&r);
+    let mut history = vec![if b_norm == 0.0 { 0.0 } else { 1.0 }];
+    let mut converged = b_norm == 0.0;
+    let mut iterations = 0;
+    let (mut rho, mut alpha, mut omega) = (1.0, 1.0, 1.0);
+    let mut p = vec![0.0; n];
+    let mut v = vec![0.0; n];
+    while !converged && iterations < max_iterations {
+        iterations += 1;
+        let rho_next = dot(&r_hat, &r);
+        if rho_next == 0.0 || omega == 0.0 {
+            return Err(MatrixError::Breakdown { iteration : iterations });
+        }
+        let beta = (rho_next / rho) * (alpha / omega);
+        for i in 0..n {
+            p[i] = r[i] + beta * (p[i] - omega * v[i]);
+        }
+        rho = rho_next;
+        let p_hat = preconditioner.apply(&Vector::from_vec(p.clone())).into_vec();
+        v = a.try_apply(&Vector::from_vec(p_hat.clone()))?.into_vec();
+        let denominator = dot(&r_hat, &v);
+        if denominator == 0.0 {
+            return Err(MatrixError::Breakdown { iteration : iterations });
+        }
+        alpha = rho / denominator;
+        let mut s = r.clone();
+        axpy(-alpha, &v, &mut s);
+        axpy(alpha, &p_hat, &mut x);
+        let relative = norm(&s) / b_norm;
+        if relative <= tol {
+            history.push(relative);
+            converged = true;
+            break;
+        }
+        let s_hat = preconditioner.apply(&Vector::from_vec(s.clone())).into_vec();
+        let t = a.try_apply(&Vector::from_vec(s_hat.clone()))?.into_vec();
+        let tt = dot(&t, &t);
+        if tt == 0.0 {
+            return Err(MatrixError::Breakdown { iteration : iterations });
+        }
+        omega = dot(&t, &s) / tt;
+        axpy(omega, &s_hat, &mut x);
+        r = s;
+        axpy(-omega, &t, &mut r);
+        let relative = norm(&r) / b_norm;
+        history.push(relative);
+        converged = relative <= tol;
+    }
+    Ok(IterativeSolution {
+        solution : Vector::from_vec(x),
+        iterations : iterations,
+        residual_history : history,
+        converged : converged,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix_base::*;
+    use crate::sparse_matrix::SparseMatrix;
+    use crate::dense_matrix::DenseMatrix;
+    use crate::preconditioner::IdentityPreconditioner;
+    use crate::triplet::TripletBuilder;
+
+    /// The 5-point Laplacian on a `k x k` grid with Dirichlet boundary.
+    pub(crate) fn poisson_2d(k : usize) -> SparseMatrix<f64> {
+        let mut t = TripletBuilder::new(k * k, k * k);
+        for i in 0..k {
+            for j in 0..k {
+                let row = i * k + j;
+                t.push(row, row, 4.0);
+                if i > 0 {
+                    t.push(row, row - k, -1.0);
+                }
+                if i + 1 < k {
+                    t.push(row, row + k, -1.0);
+                }
+                if j > 0 {
+                    t.push(row, row - 1, -1.0);
+                }
+                if j + 1 < k {
+                    t.push(row, row + 1, -1.0);
+                }
+            }
+        }
+        t.into_sparse()
+    }
+
+    #[test]
+    fn cg_poisson() {
+        let a = poisson_2d(10);
+        let b = Vector::new_with(100, 1.0);
+        let result = conjugate_gradient(&a, &b, &IdentityPreconditioner, 1e-10, 200);
+        assert!(result.is_converged());
+        assert_eq!(result.get_residual_history().len(), result.get_iterations() + 1);
+        assert!(*result.get_residual_history().last().unwrap() <= 1e-10);
+        let ax = a.mul_vector(result.get_solution());
+        for i in 0..100 {
+            assert!((ax[i] - 1.0).abs() < 1e-8);
+        }
+        // CG on n unknowns converges in at most n steps in exact arithmetic,
+        // and much faster here
+        assert!(result.get_iterations() < 100);
+        let capped = conjugate_gradient(&a, &b, &IdentityPreconditioner, 1e-10, 3);
+        assert!(!capped.is_converged());
+        assert_eq!(capped.get_iterations(), 3);
+    }
+
+    #[test]
+    fn cg_errors() {
+        let mut indefinite = SparseMatrix::new(2, 2);
+        indefinite.set(0, 0, 1.0);
+        indefinite.set(1, 1, -1.0);
+        let b = crate::vector![0.0, 1.0];
+        assert_eq!(try_conjugate_gradient(&indefinite, &b, &IdentityPreconditioner, 1e-10, 10).unwrap_err(), MatrixError::Breakdown { iteration : 1 });
+        let short = crate::vector![1.0];
+        assert!(try_conjugate_gradient(&indefinite, &short, &IdentityPreconditioner, 1e-10, 10).is_err());
+        let zero = conjugate_gradient(&indefinite, &crate::vector![0.0, 0.0], &IdentityPreconditioner, 1e-10, 10);
+        assert!(zero.is_converged() && zero.get_iterations() == 0);
+    }
+
+    /// `poisson_2d` plus an upwinded convection term along the grid rows,
+    /// which makes it nonsymmetric.
+    fn convection_diffusion(k : usize, c : f64) -> SparseMatrix<f64> {
+        let mut a = poisson_2d(k);
+        for row in 0..(k * k) {
+            a.add(row, row, c);
+            if row % k > 0 {
+                a.add(row, row - 1, -c);
+            }
+        }
+        a
+    }
+
+    fn assert_solves(a : &SparseMatrix<f64>, b : &Vector<f64>, result : &IterativeSolution) {
+        assert!(result.is_converged());
+        assert_eq!(result.get_residual_history().len(), result.get_iterations() + 1);
+        let ax = a.mul_vector(result.get_solution());
+        for i in 0..b.length() {
+            assert!((ax[i] - b[i]).abs() < 1e-7, "{} != {}", ax[i], b[i]);
+        }
+    }
+
+    #[test]
+    fn gmres_nonsymmetric() {
+        let a = convection_diffusion(8, 3.0);
+        let b = Vector::from_vec((0..64).map(|i| (i % 5) as f64).collect());
+        let full = gmres(&a, &b, &IdentityPreconditioner, 64, 1e-10, 200);
+        assert_solves(&a, &b, &full);
+        let restarted = gmres(&a, &b, &IdentityPreconditioner, 5, 1e-10, 2000);
+        assert_solves(&a, &b, &restarted);
+        assert!(restarted.get_iterations() >= full.get_iterations());
+        let capped = gmres(&a, &b, &IdentityPreconditioner, 5, 1e-10, 7);
+        assert!(!capped.is_converged());
+        assert_eq!(capped.get_iterations(), 7);
+        assert_eq!(try_gmres(&a, &b, &IdentityPreconditioner, 0, 1e-10, 200).unwrap_err(), MatrixError::InvalidArgument { reason : "restart must be positive" });
+    }
+
+    #[test]
+    fn bicgstab_nonsymmetric() {
+        let a = convection_diffusion(8, 3.0);
+        let b = Vector::from_vec((0..64).map(|i| (i % 5) as f64).collect());
+        let result = bicgstab(&a, &b, &IdentityPreconditioner, 1e-10, 200);
+        assert_solves(&a, &b, &result);
+        // r̂ᵀ * A * p vanishes on the first step
+        let mut swap = SparseMatrix::new(2, 2);
+        swap.set(0, 1, 1.0);
+        swap.set(1, 0, 1.0);
+        let b = crate::vector![1.0, 0.0];
+        assert_eq!(try_bicgstab(&swap, &b, &IdentityPreconditioner, 1e-10, 10).unwrap_err(), MatrixError::Breakdown { iteration : 1 });
+        // GMRES has no such breakdown
+        assert_solves(&swap, &b, &gmres(&swap, &b, &IdentityPreconditioner, 2, 1e-12, 10));
+    }
+
+    #[test]
+    fn preconditioning_saves_iterations() {
+        use crate::preconditioner::{Jacobi, SSOR, ILU0, IC0};
+        let a = poisson_2d(12);
+        let b = Vector::from_vec((0..144).map(|i| (i % 7) as f64 - 3.0).collect());
+        let plain = conjugate_gradient(&a, &b, &IdentityPreconditioner, 1e-10, 500).get_iterations();
+        let jacobi = conjugate_gradient(&a, &b, &Jacobi::new(&a), 1e-10, 500);
+        assert!(jacobi.is_converged() && jacobi.get_iterations() <= plain);
+        for result in [conjugate_gradient(&a, &b, &SSOR::new(&a, 1.2), 1e-10, 500),
+                       conjugate_gradient(&a, &b, &IC0::new(&a), 1e-10, 500)].iter() {
+            assert_solves(&a, &b, result);
+            assert!(result.get_iterations() < plain);
+        }
+        let c = convection_diffusion(12, 3.0);
+        let plain = bicgstab(&c, &b, &IdentityPreconditioner, 1e-10, 500).get_iterations();
+        let ilu = ILU0::new(&c);
+        let result = bicgstab(&c, &b, &ilu, 1e-10, 500);
+        assert_solves(&c, &b, &result);
+        assert!(result.get_iterations() < plain);
+        let result = gmres(&c, &b, &ilu, 20, 1e-10, 500);
+        assert_solves(&c, &b, &result);
+    }
+
+    #[test]
+    fn solvers_take_any_operator() {
+        use crate::linear_operator::FnOperator;
+        let a = poisson_2d(6);
+        let b = Vector::from_vec((0..36).map(|i| (i % 4) as f64).collect());
+        let stencil = FnOperator::new(36, 36, |x : &Vector<f64>| a.mul_vector(x));
+        let mut dense = DenseMatrix::new(36, 36);
+        dense.set_from_matrix(0, 0, &a);
+        let reference = conjugate_gradient(&a, &b, &IdentityPreconditioner, 1e-12, 100);
+        for result in [conjugate_gradient(&stencil, &b, &IdentityPreconditioner, 1e-12, 100),
+                       conjugate_gradient(&dense, &b, &IdentityPreconditioner, 1e-12, 100)].iter() {
+            assert_solves(&a, &b, result);
+            assert_eq!(result.get_iterations(), reference.get_iterations());
+        }
+        assert_solves(&a, &b, &gmres(&stencil, &b, &IdentityPreconditioner, 10, 1e-12, 200));
+        assert_solves(&a, &b, &bicgstab(&dense, &b, &IdentityPreconditioner, 1e-12, 200));
+        // a closure returning the wrong length is reported, not truncated
+        let short = FnOperator::new(36, 36, |x : &Vector<f64>| Vector::from_vec(x.as_slice()[1..].to_vec()));
+        let mismatch = MatrixError::DimensionMismatch { left : (36, 1), right : (35, 1) };
+        assert_eq!(try_conjugate_gradient(&short, &b, &IdentityPreconditioner, 1e-12, 100).unwrap_err(), mismatch);
+        assert_eq!(try_gmres(&short, &b, &IdentityPreconditioner, 10, 1e-12, 100).unwrap_err(), mismatch);
+        assert_eq!(try_bicgstab(&short, &b, &IdentityPreconditioner, 1e-12, 100).unwrap_err(), mismatch);
+    }
+}
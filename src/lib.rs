@@ -0,0 +1,62 @@
+//! Dense and sparse matrices over generic element types.
+//!
+//! The storage types (`DenseMatrix`, `SparseMatrix`) share the `MatrixInit`,
+//! `ConstMatrix` and `Matrix` traits, so the generic algorithms in
+//! `matrix_base` (such as `inverse`) work for either of them.
+
+// The crate consistently spells out `self : &Self` and `field : field`, and
+// keeps explicit `return`s; keep clippy quiet about those house-style choices.
+#![allow(clippy::needless_arbitrary_self_type)]
+#![allow(clippy::redundant_field_names)]
+#![allow(clippy::needless_return)]
+#![allow(clippy::write_with_newline)]
+#![allow(clippy::len_zero)]
+// Numerical kernels index several arrays with the same loop variable.
+#![allow(clippy::needless_range_loop)]
+
+pub mod error;
+pub mod matrix_base;
+pub mod dense_matrix;
+pub mod sparse_matrix;
+pub mod csr_matrix;
+pub mod csc_matrix;
+pub mod triplet;
+pub mod vector;
+pub mod complex;
+pub mod semiring;
+pub mod rational;
+pub mod modp;
+pub mod bit_matrix;
+pub mod lu;
+pub mod qr;
+pub mod cholesky;
+pub mod eigen;
+pub mod svd;
+pub mod linear_operator;
+pub mod preconditioner;
+pub mod iterative;
+pub mod sparse_lu;
+
+pub use error::MatrixError;
+pub use dense_matrix::DenseMatrix;
+pub use sparse_matrix::SparseMatrix;
+pub use csr_matrix::CsrMatrix;
+pub use csc_matrix::CscMatrix;
+pub use triplet::TripletBuilder;
+pub use vector::Vector;
+pub use complex::Complex;
+pub use semiring::{Boolean, Tropical};
+pub use rational::Rational;
+pub use modp::ModP;
+pub use bit_matrix::{BitMatrix, GF2};
+pub use lu::LU;
+pub use qr::QR;
+pub use cholesky::{Cholesky, LDLT};
+pub use eigen::{SymmetricEigen, Hessenberg, Eigen, RealSchur};
+pub use svd::SVD;
+pub use linear_operator::{LinearOperator, FnOperator};
+pub use preconditioner::{Preconditioner, IdentityPreconditioner, Jacobi, SSOR, ILU0, IC0};
+pub use iterative::IterativeSolution;
+pub use sparse_lu::{SymbolicLU, SparseLU, ColumnOrdering, FillStatistics};
+pub use matrix_base::{AddMonoid, Semiring, Ring, Field, Group, Magnitude};
+pub use matrix_base::{MatrixInit, ConstMatrix, MatrixIterator, Matrix};
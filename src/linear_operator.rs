@@ -0,0 +1,178 @@
+use crate::matrix_base::*;
+use crate::dense_matrix::DenseMatrix;
+use crate::sparse_matrix::SparseMatrix;
+use crate::vector::Vector;
+use crate::error::*;
+
+/// Anything that can compute `y = A * x` for real vectors, whether or not
+/// `A` is stored. Krylov solvers and the Lanczos eigensolver only ever touch
+/// `A` through this trait.
+pub trait LinearOperator {
+    /// `(rows, columns)` of `A`.
+    fn get_shape(self : &Self) -> (usize, usize);
+
+    /// `A * x`; `x` must have `columns` entries.
+    fn apply(self : &Self, x : &Vector<f64>) -> Vector<f64>;
+
+    /// `Aᵀ * x`, or `None` when the operator cannot provide it.
+    fn apply_transpose(self : &Self, _x : &Vector<f64>) -> Option<Vector<f64>> {
+        None
+    }
+
+    /// `A * x`, checking that `x` has `columns` entries and that the result
+    /// has `rows`.
+    fn try_apply(self : &Self, x : &Vector<f64>) -> Result<Vector<f64>, MatrixError> {
+        let shape = self.get_shape();
+        if x.length() != shape.1 {
+            return Err(MatrixError::DimensionMismatch { left : shape, right : (x.length(), 1) });
+        }
+        check_output(shape, self.apply(x))
+    }
+}
+
+fn check_output(shape : (usize, usize), y : Vector<f64>) -> Result<Vector<f64>, MatrixError> {
+    if y.length() != shape.0 {
+        return Err(MatrixError::DimensionMismatch { left : (shape.0, 1), right : (y.length(), 1) });
+    }
+    Ok(y)
+}
+
+impl LinearOperator for DenseMatrix<f64> {
+    fn get_shape(self : &Self) -> (usize, usize) {
+        (self.get_row(), self.get_column())
+    }
+
+    fn apply(self : &Self, x : &Vector<f64>) -> Vector<f64> {
+        let col = self.get_column();
+        if x.length() != col {
+            panic!("linear operator error : {}", MatrixError::DimensionMismatch { left : self.get_shape(), right : (x.length(), 1) });
+        }
+        let (data, x) = (self.as_slice(), x.as_slice());
+        Vector::from_vec((0..self.get_row())
+            .map(|i| data[i * col..(i + 1) * col].iter().zip(x.iter()).map(|(a, b)| a * b).sum()).collect())
+    }
+
+    fn apply_transpose(self : &Self, x : &Vector<f64>) -> Option<Vector<f64>> {
+        Some(self.transpose().apply(x))
+    }
+}
+
+impl LinearOperator for SparseMatrix<f64> {
+    fn get_shape(self : &Self) -> (usize, usize) {
+        (self.get_row(), self.get_column())
+    }
+
+    fn apply(self : &Self, x : &Vector<f64>) -> Vector<f64> {
+        self.mul_vector(x)
+    }
+
+    /// Scatters row `i` scaled by `x[i]`, so no transposed copy is built.
+    fn apply_transpose(self : &Self, x : &Vector<f64>) -> Option<Vector<f64>> {
+        if x.length() != self.get_row() {
+            panic!("linear operator error : {}", MatrixError::DimensionMismatch { left : (self.get_column(), self.get_row()), right : (x.length(), 1) });
+        }
+        let mut y = vec![0.0; self.get_column()];
+        for i in 0..self.get_row() {
+            for e in self.get_iterator(i) {
+                y[e.get_col()] += e.get_v() * x[i];
+            }
+        }
+        Some(Vector::from_vec(y))
+    }
+}
+
+type BoxedApply = Box<dyn Fn(&Vector<f64>) -> Vector<f64>>;
+
+/// A matrix-free operator defined by a closure, for stencils, Kronecker
+/// products, FFT-based convolutions and the like.
+pub struct FnOperator<F : Fn(&Vector<f64>) -> Vector<f64>> {
+    row : usize,
+    col : usize,
+    apply : F,
+    transpose : Option<BoxedApply>,
+}
+
+impl<F : Fn(&Vector<f64>) -> Vector<f64>> FnOperator<F> {
+    pub fn new(row : usize, col : usize, apply : F) -> FnOperator<F> {
+        FnOperator {
+            row : row,
+            col : col,
+            apply : apply,
+            transpose : None,
+        }
+    }
+
+    /// Supplies `Aᵀ * x` as well.
+    pub fn with_transpose<G : Fn(&Vector<f64>) -> Vector<f64> + 'static>(self : Self, transpose : G) -> FnOperator<F> {
+        FnOperator {
+            transpose : Some(Box::new(transpose)),
+            ..self
+        }
+    }
+}
+
+impl<F : Fn(&Vector<f64>) -> Vector<f64>> LinearOperator for FnOperator<F> {
+    fn get_shape(self : &Self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    /// Panics if the closure does not return `rows` entries.
+    fn apply(self : &Self, x : &Vector<f64>) -> Vector<f64> {
+        self.try_apply(x).unwrap_or_else(|e| panic!("linear operator error : {}", e))
+    }
+
+    fn try_apply(self : &Self, x : &Vector<f64>) -> Result<Vector<f64>, MatrixError> {
+        if x.length() != self.col {
+            return Err(MatrixError::DimensionMismatch { left : (self.row, self.col), right : (x.length(), 1) });
+        }
+        check_output((self.row, self.col), (self.apply)(x))
+    }
+
+    fn apply_transpose(self : &Self, x : &Vector<f64>) -> Option<Vector<f64>> {
+        self.transpose.as_ref().map(|t| t(x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{matrix, vector};
+
+    #[test]
+    fn operators_agree() {
+        let dense = matrix![DenseMatrix<f64> => (1.0, 0.0, 2.0);(0.0, 3.0, 0.0)];
+        let mut sparse = SparseMatrix::new(2, 3);
+        sparse.set(0, 0, 1.0);
+        sparse.set(0, 2, 2.0);
+        sparse.set(1, 1, 3.0);
+        let x = vector![1.0, 2.0, 3.0];
+        let y = vector![1.0, -1.0];
+        assert_eq!(dense.get_shape(), (2, 3));
+        assert_eq!(dense.apply(&x), vector![7.0, 6.0]);
+        assert_eq!(sparse.apply(&x), vector![7.0, 6.0]);
+        assert_eq!(dense.apply_transpose(&y), Some(vector![1.0, -3.0, 2.0]));
+        assert_eq!(sparse.apply_transpose(&y), Some(vector![1.0, -3.0, 2.0]));
+        assert_eq!(sparse.try_apply(&y).unwrap_err(), MatrixError::DimensionMismatch { left : (2, 3), right : (2, 1) });
+        let empty = DenseMatrix::<f64>::new(3, 0);
+        assert_eq!(empty.apply(&Vector::new_with(0, 0.0)), vector![0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn closure_operator() {
+        // the 1-D Laplacian stencil, never stored
+        let n = 5;
+        let laplacian = FnOperator::new(n, n, move |x : &Vector<f64>| {
+            Vector::from_vec((0..n).map(|i| {
+                let left = if i > 0 { x[i - 1] } else { 0.0 };
+                let right = if i + 1 < n { x[i + 1] } else { 0.0 };
+                2.0 * x[i] - left - right
+            }).collect())
+        });
+        assert_eq!(laplacian.apply(&vector![1.0, 1.0, 1.0, 1.0, 1.0]), vector![1.0, 0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(laplacian.apply_transpose(&vector![1.0, 1.0, 1.0, 1.0, 1.0]), None);
+        let doubling = FnOperator::new(1, 1, |x : &Vector<f64>| vector![2.0 * x[0]]).with_transpose(|x : &Vector<f64>| vector![2.0 * x[0]]);
+        assert_eq!(doubling.apply_transpose(&vector![3.0]), Some(vector![6.0]));
+        let short = FnOperator::new(2, 2, |x : &Vector<f64>| vector![x[0]]);
+        assert_eq!(short.try_apply(&vector![1.0, 2.0]).unwrap_err(), MatrixError::DimensionMismatch { left : (2, 1), right : (1, 1) });
+    }
+}
@@ -0,0 +1,243 @@
+use std::fmt::Display;
+
+use crate::matrix_base::*;
+use crate::dense_matrix::DenseMatrix;
+use crate::vector::Vector;
+use crate::error::*;
+
+/// LU factorization with partial pivoting: `P * A = L * U`.
+///
+/// `L` (unit lower triangular, diagonal not stored) and `U` share one
+/// matrix. Row `i` of `P * A` is row `perm[i]` of `A`. Factor once, then
+/// reuse the result for any number of right-hand sides.
+#[derive(Clone, Debug)]
+pub struct LU<T> {
+    lu : DenseMatrix<T>,
+    perm : Vec<usize>,
+    swaps : usize,
+    singular : bool,
+}
+
+impl<T : Field + Magnitude + Default + Copy + Display> LU<T> {
+    pub fn new(m : &DenseMatrix<T>) -> LU<T> {
+        LU::try_new(m).unwrap_or_else(|e| panic!("lu decomposition error : {}", e))
+    }
+
+    /// Factorizes `m`. Only a non-square `m` is an error: a singular `m`
+    /// still factorizes, and `is_singular` reports it.
+    pub fn try_new(m : &DenseMatrix<T>) -> Result<LU<T>, MatrixError> {
+        check_square((m.get_row(), m.get_column()))?;
+        let n = m.get_row();
+        let mut lu = m.clone();
+        let mut perm : Vec<usize> = (0..n).collect();
+        let mut swaps = 0;
+        let mut singular = false;
+        for k in 0..n {
+            let mut p = k;
+            let mut max = lu.get(k, k).unwrap().magnitude();
+            for i in (k + 1)..n {
+                let v = lu.get(i, k).unwrap().magnitude();
+                if v > max {
+                    max = v;
+                    p = i;
+                }
+            }
+            if *lu.get(p, k).unwrap() == T::get_identity_add() {
+                // the whole column is zero below the diagonal, nothing to
+                // eliminate; U gets a zero pivot.
+                singular = true;
+                continue;
+            }
+            if p != k {
+                lu.element_row_transform_swap(p, k);
+                perm.swap(p, k);
+                swaps += 1;
+            }
+            let pivot_inverse = lu.get(k, k).unwrap().get_inverse_mul();
+            for i in (k + 1)..n {
+                let l = *lu.get(i, k).unwrap() * pivot_inverse;
+                if l == T::get_identity_add() {
+                    continue;
+                }
+                lu.set(i, k, l);
+                for j in (k + 1)..n {
+                    let v = *lu.get(i, j).unwrap() - l * *lu.get(k, j).unwrap();
+                    lu.set(i, j, v);
+                }
+            }
+        }
+        Ok(LU {
+            lu : lu,
+            perm : perm,
+            swaps : swaps,
+            singular : singular,
+        })
+    }
+
+    pub fn is_singular(self : &Self) -> bool {
+        self.singular
+    }
+
+    pub fn get_permutation(self : &Self) -> &[usize] {
+        &self.perm
+    }
+
+    pub fn get_l(self : &Self) -> DenseMatrix<T> {
+        let n = self.lu.get_row();
+        let mut l = DenseMatrix::new_with(n, n, T::get_identity_add());
+        for i in 0..n {
+            for j in 0..i {
+                l.set(i, j, *self.lu.get(i, j).unwrap());
+            }
+            l.set(i, i, T::get_identity_mul());
+        }
+        l
+    }
+
+    pub fn get_u(self : &Self) -> DenseMatrix<T> {
+        let n = self.lu.get_row();
+        let mut u = DenseMatrix::new_with(n, n, T::get_identity_add());
+        for i in 0..n {
+            for j in i..n {
+                u.set(i, j, *self.lu.get(i, j).unwrap());
+            }
+        }
+        u
+    }
+
+    pub fn determinant(self : &Self) -> T {
+        let mut det = T::get_identity_mul();
+        for i in 0..self.lu.get_row() {
+            det = det * *self.lu.get(i, i).unwrap();
+        }
+        if self.swaps % 2 == 1 {
+            det.get_inverse_add()
+        } else {
+            det
+        }
+    }
+
+    /// Solves `A * x = b`, or returns `None` if `A` is singular.
+    pub fn solve(self : &Self, b : &Vector<T>) -> Option<Vector<T>> {
+        match self.try_solve(b) {
+            Ok(x) => Some(x),
+            Err(MatrixError::Singular) => None,
+            Err(e) => panic!("lu solve error : {}", e),
+        }
+    }
+
+    pub fn try_solve(self : &Self, b : &Vector<T>) -> Result<Vector<T>, MatrixError> {
+        let n = self.lu.get_row();
+        if b.length() != n {
+            return Err(MatrixError::DimensionMismatch { left : (n, n), right : (b.length(), 1) });
+        }
+        if self.singular {
+            return Err(MatrixError::Singular);
+        }
+        let mut x = Vector::new_with(n, T::get_identity_add());
+        // forward substitution with the unit lower triangle
+        for i in 0..n {
+            let mut v = b[self.perm[i]];
+            for j in 0..i {
+                v = v - *self.lu.get(i, j).unwrap() * x[j];
+            }
+            x.set(i, v);
+        }
+        // back substitution with the upper triangle
+        for i in (0..n).rev() {
+            let mut v = x[i];
+            for j in (i + 1)..n {
+                v = v - *self.lu.get(i, j).unwrap() * x[j];
+            }
+            x.set(i, v * self.lu.get(i, i).unwrap().get_inverse_mul());
+        }
+        Ok(x)
+    }
+
+    /// Solves `A * X = B` column by column.
+    pub fn solve_matrix(self : &Self, b : &DenseMatrix<T>) -> Option<DenseMatrix<T>> {
+        match self.try_solve_matrix(b) {
+            Ok(x) => Some(x),
+            Err(MatrixError::Singular) => None,
+            Err(e) => panic!("lu solve error : {}", e),
+        }
+    }
+
+    pub fn try_solve_matrix(self : &Self, b : &DenseMatrix<T>) -> Result<DenseMatrix<T>, MatrixError> {
+        let n = self.lu.get_row();
+        if b.get_row() != n {
+            return Err(MatrixError::DimensionMismatch { left : (n, n), right : (b.get_row(), b.get_column()) });
+        }
+        let mut x = DenseMatrix::new_with(b.get_row(), b.get_column(), T::get_identity_add());
+        for j in 0..b.get_column() {
+            x.set_nth_column(j, self.try_solve(&b.get_nth_column(j))?);
+        }
+        Ok(x)
+    }
+
+    pub fn inverse(self : &Self) -> Option<DenseMatrix<T>> {
+        self.try_inverse().ok()
+    }
+
+    pub fn try_inverse(self : &Self) -> Result<DenseMatrix<T>, MatrixError> {
+        self.try_solve_matrix(&DenseMatrix::get_identity_matrix(self.lu.get_row()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{matrix, vector};
+
+    fn assert_close(a : &DenseMatrix<f64>, b : &DenseMatrix<f64>) {
+        for i in 0..a.get_row() {
+            for j in 0..a.get_column() {
+                assert!((a.get(i, j).unwrap() - b.get(i, j).unwrap()).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn lu_factors() {
+        let a = matrix![DenseMatrix<f64> => (1.0, 2.0, 3.0);(2.0, 2.0, 1.0);(3.0, 4.0, 3.0)];
+        let lu = LU::new(&a);
+        assert!(!lu.is_singular());
+        // pivots on the largest entry of the first column
+        assert_eq!(lu.get_permutation()[0], 2);
+        let mut pa = a.clone();
+        for i in 0..3 {
+            for j in 0..3 {
+                pa.set(i, j, *a.get(lu.get_permutation()[i], j).unwrap());
+            }
+        }
+        assert_close(&(&lu.get_l() * &lu.get_u()), &pa);
+        assert!((lu.determinant() - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn lu_solve_and_inverse() {
+        let a = matrix![DenseMatrix<f64> => (0.0, 1.0);(2.0, 3.0)];
+        let lu = LU::new(&a);
+        let x = lu.solve(&vector![1.0, 8.0]).unwrap();
+        assert!((x[0] - 2.5).abs() < 1e-12);
+        assert!((x[1] - 1.0).abs() < 1e-12);
+        assert_close(&(&a * &lu.inverse().unwrap()), &DenseMatrix::get_identity_matrix(2));
+    }
+
+    #[test]
+    fn lu_singular() {
+        let a = matrix![DenseMatrix<f64> => (1.0, 2.0);(2.0, 4.0)];
+        let lu = LU::new(&a);
+        assert!(lu.is_singular());
+        assert_eq!(lu.determinant(), 0.0);
+        assert!(lu.solve(&vector![1.0, 1.0]).is_none());
+        assert_eq!(lu.try_solve(&vector![1.0]).unwrap_err(), MatrixError::DimensionMismatch { left : (2, 2), right : (1, 1) });
+        assert_eq!(lu.try_inverse().unwrap_err(), MatrixError::Singular);
+    }
+
+    #[test]
+    fn lu_not_square() {
+        let a : DenseMatrix<f64> = DenseMatrix::new(2, 3);
+        assert_eq!(LU::try_new(&a).unwrap_err(), MatrixError::NotSquare { shape : (2, 3) });
+    }
+}
@@ -1,12 +1,5 @@
-
-mod dense_matrix;
-mod sparse_matrix;
-mod matrix_base;
-mod vector;
-
-use dense_matrix::DenseMatrix;
-use sparse_matrix::SparseMatrix;
-use matrix_base::*;
+use matrix::{matrix, DenseMatrix, SparseMatrix};
+use matrix::{ConstMatrix, Matrix, MatrixInit};
 
 fn main() {
     let m = matrix![DenseMatrix<f32> => (1.0, 2.0, 3.0);(2.0, 2.0, 1.0);(3.0, 4.0, 3.0)];
@@ -20,4 +13,5 @@ fn main() {
     m.set(2, 24, 15.0);
     m.element_row_transform_swap(105, 2);
     m.element_row_transform_plus(2, 105, 2.0);
-}
\ No newline at end of file
+    println!("m[2, 25] = {}", m.get(2, 25).unwrap());
+}
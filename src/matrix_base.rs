@@ -1,30 +1,96 @@
-use std::ops::Mul;
+use std::ops::{Add, Sub, Mul, Div, Neg};
 use std::cmp::PartialEq;
-pub trait Group<T : Mul<T, Output = T>> {
-    fn get_inverse_mul(self : &Self) -> T;
-    fn get_identity_mul() -> T;
-    fn get_identity_add() -> T;
-    fn get_inverse_add(self : &Self) -> T;
+
+use crate::error::*;
+
+/// A set with an associative `+` and an additive identity (zero).
+pub trait AddMonoid : Add<Output = Self> + Clone + PartialEq + Sized {
+    fn get_identity_add() -> Self;
 }
 
-impl Group<f32> for f32 {
-    fn get_identity_mul() -> f32 {
-        1.0
-    }
+/// An additive monoid with an associative `*` that distributes over `+`.
+/// This is all that `set`/`add` and matrix products need, so boolean and
+/// tropical matrices work as well as numeric ones.
+pub trait Semiring : AddMonoid + Mul<Output = Self> {
+    fn get_identity_mul() -> Self;
+}
 
-    fn get_inverse_mul(self : &Self) -> f32 {
-        Self::get_identity_mul() / *self
+/// A semiring where every element has an additive inverse.
+pub trait Ring : Semiring + Sub<Output = Self> + Neg<Output = Self> {
+    fn get_inverse_add(self : &Self) -> Self {
+        -self.clone()
     }
+}
 
-    fn get_identity_add() -> f32 {
-        0.0
+/// A ring where every non-zero element has a multiplicative inverse.
+pub trait Field : Ring + Div<Output = Self> {
+    fn get_inverse_mul(self : &Self) -> Self {
+        Self::get_identity_mul() / self.clone()
     }
+}
 
-    fn get_inverse_add(self : &Self) -> f32 {
-        Self::get_identity_add() - *self
-    }
+/// The original element trait, kept so existing bounds keep compiling.
+/// It is now just another name for `Field`.
+pub trait Group<T : Mul<T, Output = T>> : Field {}
+
+impl<T : Field> Group<T> for T {}
+
+/// A non-negative size for an element, used to pick pivots: elimination is
+/// most stable when it divides by the entry of largest magnitude.
+pub trait Magnitude {
+    fn magnitude(self : &Self) -> f64;
 }
 
+macro_rules! impl_field_for_float {
+    ($($t : ty),+) => {$(
+        impl AddMonoid for $t {
+            fn get_identity_add() -> $t {
+                0.0
+            }
+        }
+
+        impl Semiring for $t {
+            fn get_identity_mul() -> $t {
+                1.0
+            }
+        }
+
+        impl Ring for $t {}
+
+        impl Field for $t {}
+
+        impl Magnitude for $t {
+            fn magnitude(self : &Self) -> f64 {
+                self.abs() as f64
+            }
+        }
+    )+};
+}
+
+impl_field_for_float!(f32, f64);
+
+// Integers are a ring but not a field: they have no multiplicative inverse,
+// so integer matrices cannot call `inverse`.
+macro_rules! impl_ring_for_int {
+    ($($t : ty),+) => {$(
+        impl AddMonoid for $t {
+            fn get_identity_add() -> $t {
+                0
+            }
+        }
+
+        impl Semiring for $t {
+            fn get_identity_mul() -> $t {
+                1
+            }
+        }
+
+        impl Ring for $t {}
+    )+};
+}
+
+impl_ring_for_int!(i8, i16, i32, i64, i128, isize);
+
 pub trait MatrixInit<T> {
     fn new(row : usize, col : usize) -> Self;
 }
@@ -34,25 +100,82 @@ pub trait ConstMatrix<T> {
     fn get_column(&self) ->usize;
     fn get(&self, row : usize, col : usize) -> Option<&T>;
     fn get_sub_matrix(&self, row_begin : usize, row : usize, col_begin : usize, col : usize) -> Self;
+
+    /// The value at `[row, col]`, reporting an entry a sparse matrix does not
+    /// store as zero. Generic algorithms should read through this rather
+    /// than `get`, which returns `None` for such entries.
+    fn get_or_zero(&self, row : usize, col : usize) -> T where T : AddMonoid {
+        match self.get(row, col) {
+            Some(v) => v.clone(),
+            None => T::get_identity_add(),
+        }
+    }
+
+    /// Like `get`, but reports an out of range index instead of panicking.
+    /// `Ok(None)` means an entry that a sparse matrix does not store.
+    fn try_get(&self, row : usize, col : usize) -> Result<Option<&T>, MatrixError> {
+        check_index(row, col, (self.get_row(), self.get_column()))?;
+        Ok(self.get(row, col))
+    }
+
+    fn try_get_sub_matrix(&self, row_begin : usize, row : usize, col_begin : usize, col : usize) -> Result<Self, MatrixError> where Self : Sized {
+        if row_begin + row > self.get_row() || col_begin + col > self.get_column() {
+            return Err(MatrixError::IndexOutOfBounds {
+                index : (row_begin + row, col_begin + col),
+                shape : (self.get_row(), self.get_column()),
+            });
+        }
+        Ok(self.get_sub_matrix(row_begin, row, col_begin, col))
+    }
 }
 
 pub trait MatrixIterator<'a, T : Iterator + 'a> {
     fn get_iterator<'b : 'a>(self : &'b Self, row : usize) -> T;
 }
 
-pub trait Matrix<T : Mul<Output = T> + Group<T> + PartialEq + Clone> : MatrixInit<T> + ConstMatrix<T> + Clone + Sized {
+pub trait Matrix<T : Semiring> : MatrixInit<T> + ConstMatrix<T> + Clone + Sized {
     fn set(&mut self, row : usize, col : usize, value : T);
     fn add(&mut self, row : usize, col : usize, value : T);
     fn element_row_transform_swap(&mut self, row_i : usize, row_j : usize);
     fn element_row_transform_multi(&mut self, row : usize, k : T);
     fn element_row_transform_plus(&mut self, row_i : usize, row_j : usize, k : T);
 
+    fn try_set(&mut self, row : usize, col : usize, value : T) -> Result<(), MatrixError> {
+        check_index(row, col, (self.get_row(), self.get_column()))?;
+        self.set(row, col, value);
+        Ok(())
+    }
+
+    fn try_add(&mut self, row : usize, col : usize, value : T) -> Result<(), MatrixError> {
+        check_index(row, col, (self.get_row(), self.get_column()))?;
+        self.add(row, col, value);
+        Ok(())
+    }
+
+    fn try_element_row_transform_swap(&mut self, row_i : usize, row_j : usize) -> Result<(), MatrixError> {
+        check_index(row_i.max(row_j), 0, (self.get_row(), 1))?;
+        self.element_row_transform_swap(row_i, row_j);
+        Ok(())
+    }
+
+    fn try_element_row_transform_multi(&mut self, row : usize, k : T) -> Result<(), MatrixError> {
+        check_index(row, 0, (self.get_row(), 1))?;
+        self.element_row_transform_multi(row, k);
+        Ok(())
+    }
+
+    fn try_element_row_transform_plus(&mut self, row_i : usize, row_j : usize, k : T) -> Result<(), MatrixError> {
+        check_index(row_i.max(row_j), 0, (self.get_row(), 1))?;
+        self.element_row_transform_plus(row_i, row_j, k);
+        Ok(())
+    }
+
     fn set_from_matrix<T2 : ConstMatrix<T>>(self : &mut Self, row_begin : usize, col_begin : usize, m : &T2) {
         for i in 0..m.get_row() {
             for j in 0..m.get_column() {
                 let s_i = i + row_begin;
                 let s_j = j + col_begin;
-                self.set(s_i, s_j, m.get(i, j).unwrap().clone());
+                self.set(s_i, s_j, m.get_or_zero(i, j));
             } 
         }
     }
@@ -65,48 +188,58 @@ pub trait Matrix<T : Mul<Output = T> + Group<T> + PartialEq + Clone> : MatrixIni
         m
     }
 
-    fn inverse(self : &Self) -> Option<Self> {
-        if self.get_row() != self.get_column() {
-            panic!("matrix inverse need row == col");
+    /// Like `inverse`, but a non-square input is an error instead of a panic.
+    fn try_inverse(self : &Self) -> Result<Self, MatrixError> where T : Field {
+        check_square((self.get_row(), self.get_column()))?;
+        self.inverse().ok_or(MatrixError::Singular)
+    }
+
+    fn inverse(self : &Self) -> Option<Self> where T : Field {
+        if let Err(e) = check_square((self.get_row(), self.get_column())) {
+            panic!("matrix inverse error : {}", e);
         }
         if self.get_row() == 1 && self.get_column() == 1 {
+            let v = self.get_or_zero(0, 0);
+            if v == T::get_identity_add() {
+                return None;
+            }
             let mut m = self.clone();
-            m.set(0, 0, (*self.get(0, 0).unwrap()).get_inverse_mul() /*逆元*/);
+            m.set(0, 0, v.get_inverse_mul() /*逆元*/);
             return Some(m);
         }
         let mut myself = self.clone();
         let mut result = Self::get_identity_matrix(self.get_row());
         for i in 0..self.get_row() {
-            if *myself.get(i, i).unwrap() == T::get_identity_add() {
+            if myself.get_or_zero(i, i) == T::get_identity_add() {
                 let mut error = true;
                 for j in (i+1)..self.get_row() {
-                    if *self.get(j, i).unwrap() != T::get_identity_add() {
+                    if myself.get_or_zero(j, i) != T::get_identity_add() {
                         myself.element_row_transform_swap(i, j);
                         result.element_row_transform_swap(i, j);
                         error = false;
                         break;
                     }
                 }
-                if error == true {
+                if error {
                     return None;
                 }
             }
             for j in (i+1)..self.get_row() {
-                let j_i = myself.get(j, i).unwrap();
-                if *j_i != T::get_identity_add() {
-                    let k = ((*j_i).clone() * myself.get(i, i).unwrap().get_inverse_mul()).get_inverse_add();
+                let j_i = myself.get_or_zero(j, i);
+                if j_i != T::get_identity_add() {
+                    let k = (j_i * myself.get_or_zero(i, i).get_inverse_mul()).get_inverse_add();
                     myself.element_row_transform_plus(j, i, k.clone());
                     result.element_row_transform_plus(j, i, k.clone());
                 }
             }
-            let k = myself.get(i, i).unwrap().get_inverse_mul();
+            let k = myself.get_or_zero(i, i).get_inverse_mul();
             myself.element_row_transform_multi(i, k.clone());
             result.element_row_transform_multi(i, k.clone());
         }
         for i in 1..self.get_row() {
             let mut j : i64 = (i - 1) as i64;
             while j >= 0 {
-                let k = myself.get(j as usize, i).unwrap().get_inverse_add();
+                let k = myself.get_or_zero(j as usize, i).get_inverse_add();
                 myself.element_row_transform_plus(j as usize, i, k.clone());
                 result.element_row_transform_plus(j as usize, i, k.clone());
                 j -= 1;
@@ -114,14 +247,117 @@ pub trait Matrix<T : Mul<Output = T> + Group<T> + PartialEq + Clone> : MatrixIni
         }
         Some(result)
     }
+
+    /// Sum of the diagonal.
+    fn trace(self : &Self) -> T {
+        self.try_trace().unwrap_or_else(|e| panic!("matrix trace error : {}", e))
+    }
+
+    fn try_trace(self : &Self) -> Result<T, MatrixError> {
+        check_square((self.get_row(), self.get_column()))?;
+        Ok((0..self.get_row()).fold(T::get_identity_add(), |acc, i| acc + self.get_or_zero(i, i)))
+    }
+
+    /// Determinant by Gaussian elimination; a row swap flips the sign.
+    fn determinant(self : &Self) -> T where T : Field {
+        self.try_determinant().unwrap_or_else(|e| panic!("matrix determinant error : {}", e))
+    }
+
+    fn try_determinant(self : &Self) -> Result<T, MatrixError> where T : Field {
+        check_square((self.get_row(), self.get_column()))?;
+        let n = self.get_row();
+        let zero = T::get_identity_add();
+        let mut m = self.clone();
+        let mut det = T::get_identity_mul();
+        for c in 0..n {
+            let p = match (c..n).find(|&i| m.get_or_zero(i, c) != zero) {
+                Some(p) => p,
+                None => return Ok(zero),
+            };
+            if p != c {
+                m.element_row_transform_swap(c, p);
+                det = det.get_inverse_add();
+            }
+            let pivot = m.get_or_zero(c, c);
+            let inv = pivot.get_inverse_mul();
+            for i in (c + 1)..n {
+                let v = m.get_or_zero(i, c);
+                if v != zero {
+                    m.element_row_transform_plus(i, c, (v * inv.clone()).get_inverse_add());
+                }
+            }
+            det = det * pivot;
+        }
+        Ok(det)
+    }
+
+    /// Reduced row echelon form and the pivot column of each non-zero row.
+    ///
+    /// Pivots are the first entries that compare unequal to zero, which is
+    /// exact for exact fields; for floating point the rank this reveals is
+    /// at the mercy of rounding, and `SVD::rank` is the better tool.
+    fn rref(self : &Self) -> (Self, Vec<usize>) where T : Field {
+        let (rows, cols) = (self.get_row(), self.get_column());
+        let zero = T::get_identity_add();
+        let mut m = self.clone();
+        let mut pivots = Vec::new();
+        for c in 0..cols {
+            let r = pivots.len();
+            if r == rows {
+                break;
+            }
+            let p = match (r..rows).find(|&i| m.get_or_zero(i, c) != zero) {
+                Some(p) => p,
+                None => continue,
+            };
+            if p != r {
+                m.element_row_transform_swap(r, p);
+            }
+            let k = m.get_or_zero(r, c).get_inverse_mul();
+            m.element_row_transform_multi(r, k);
+            m.set(r, c, T::get_identity_mul());
+            for i in 0..rows {
+                let v = m.get_or_zero(i, c);
+                if i != r && v != zero {
+                    m.element_row_transform_plus(i, r, v.get_inverse_add());
+                    m.set(i, c, zero.clone());
+                }
+            }
+            pivots.push(c);
+        }
+        (m, pivots)
+    }
+
+    /// Number of pivots in `rref`.
+    fn rank(self : &Self) -> usize where T : Field {
+        self.rref().1.len()
+    }
+
+    /// A basis of `{ x : A * x = 0 }`, one vector per free column of
+    /// `rref`, returned as the columns of an `n x (n - rank)` matrix.
+    fn null_space(self : &Self) -> Self where T : Field {
+        let (r, pivots) = self.rref();
+        let cols = self.get_column();
+        let free : Vec<usize> = (0..cols).filter(|c| !pivots.contains(c)).collect();
+        let zero = T::get_identity_add();
+        let mut basis = Self::new(cols, free.len());
+        for (k, &f) in free.iter().enumerate() {
+            basis.set(f, k, T::get_identity_mul());
+            for (i, &p) in pivots.iter().enumerate() {
+                let v = r.get_or_zero(i, f);
+                if v != zero {
+                    basis.set(p, k, v.get_inverse_add());
+                }
+            }
+        }
+        basis
+    }
 }
 
 #[macro_export]
 macro_rules! matrix_row {
     (($($var : expr),+)) => {{
-        let mut v = Vec::new();
-        $(v.push($var);)+
-        v   
+        vec![$($var),+]
     }};
 }
 
@@ -132,7 +368,7 @@ macro_rules! matrix {
         let mut r : usize = 0;
         let mut c : usize = 0;
         $(
-        let v = matrix_row!($row);
+        let v = $crate::matrix_row!($row);
         if r == 0 {
             c = v.len();
         } else {
@@ -143,12 +379,12 @@ macro_rules! matrix {
         vc.push(v);
         r += 1;
         )+
-        let mut m = <$mtype>::new(r, c);
+        let mut m = <$mtype as $crate::matrix_base::MatrixInit<_>>::new(r, c);
         r = 0;
         for each_row in vc.into_iter() {
             c = 0;
             for each in each_row.into_iter() {
-                m.set(r, c, each);
+                $crate::matrix_base::Matrix::set(&mut m, r, c, each);
                 c += 1;
             }
             r += 1;
@@ -157,3 +393,99 @@ macro_rules! matrix {
     }};
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dense_matrix::DenseMatrix;
+    use crate::sparse_matrix::SparseMatrix;
+
+    #[test]
+    fn f64_matrix_inverse() {
+        let m = matrix![DenseMatrix<f64> => (2.0, 0.0);(0.0, 4.0)];
+        let inv = m.inverse().unwrap();
+        assert_eq!(*inv.get(0, 0).unwrap(), 0.5);
+        assert_eq!(*inv.get(1, 1).unwrap(), 0.25);
+    }
+
+    #[test]
+    fn try_variants() {
+        let mut m = matrix![DenseMatrix<f64> => (1.0, 2.0);(2.0, 4.0)];
+        assert_eq!(m.try_inverse().unwrap_err(), MatrixError::Singular);
+        assert_eq!(m.try_set(2, 0, 1.0).unwrap_err(), MatrixError::IndexOutOfBounds { index : (2, 0), shape : (2, 2) });
+        assert!(m.try_element_row_transform_swap(0, 3).is_err());
+        assert_eq!(m.try_get(0, 1).unwrap(), Some(&2.0));
+        let r : DenseMatrix<f64> = DenseMatrix::new(2, 3);
+        assert_eq!(r.try_inverse().unwrap_err(), MatrixError::NotSquare { shape : (2, 3) });
+        assert!(r.try_get_sub_matrix(1, 2, 0, 1).is_err());
+    }
+
+    #[test]
+    fn sparse_matrix_inverse() {
+        // structural zeros on and below the diagonal force a row swap
+        let mut m : SparseMatrix<f64> = SparseMatrix::new(3, 3);
+        m.set(0, 2, 1.0);
+        m.set(1, 0, 2.0);
+        m.set(2, 1, 4.0);
+        assert_eq!(m.get(0, 0), None);
+        assert_eq!(m.get_or_zero(0, 0), 0.0);
+        let inv = m.inverse().unwrap();
+        assert_eq!(inv.get_or_zero(0, 1), 0.5);
+        assert_eq!(inv.get_or_zero(1, 2), 0.25);
+        assert_eq!(inv.get_or_zero(2, 0), 1.0);
+        assert_eq!(inv.get_or_zero(0, 0), 0.0);
+
+        let mut d : DenseMatrix<f64> = DenseMatrix::new(4, 4);
+        d.set_from_matrix(1, 1, &m);
+        assert_eq!(*d.get(1, 3).unwrap(), 1.0);
+        assert_eq!(*d.get(1, 1).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn singular_one_by_one() {
+        let m : SparseMatrix<f64> = SparseMatrix::new(1, 1);
+        assert!(m.inverse().is_none());
+    }
+
+    #[test]
+    fn i64_matrix_row_transform() {
+        let mut m = matrix![DenseMatrix<i64> => (1, 2);(3, 4)];
+        m.element_row_transform_plus(1, 0, -3);
+        m.element_row_transform_swap(0, 1);
+        assert_eq!(*m.get(0, 0).unwrap(), 0);
+        assert_eq!(*m.get(0, 1).unwrap(), -2);
+        assert_eq!(*m.get(1, 1).unwrap(), 2);
+    }
+
+    #[test]
+    fn trace_determinant_rank() {
+        let m = matrix![DenseMatrix<f64> => (0.0, 2.0, 1.0);(1.0, 1.0, 0.0);(2.0, 0.0, 3.0)];
+        assert_eq!(m.trace(), 4.0);
+        assert!((m.determinant() - -8.0).abs() < 1e-12);
+        assert_eq!(m.rank(), 3);
+        let singular = matrix![SparseMatrix<f64> => (1.0, 2.0);(2.0, 4.0)];
+        assert_eq!(singular.determinant(), 0.0);
+        assert_eq!(singular.rank(), 1);
+        let rect = DenseMatrix::<f64>::new(2, 3);
+        assert_eq!(rect.try_trace().unwrap_err(), MatrixError::NotSquare { shape : (2, 3) });
+        assert_eq!(rect.try_determinant().unwrap_err(), MatrixError::NotSquare { shape : (2, 3) });
+        assert_eq!(rect.rank(), 0);
+    }
+
+    #[test]
+    fn rref_with_pivots() {
+        let m = matrix![DenseMatrix<f64> => (0.0, 2.0, 4.0, 2.0);(1.0, 1.0, 1.0, 0.0);(1.0, 2.0, 3.0, 1.0)];
+        let (r, pivots) = m.rref();
+        assert_eq!(pivots, vec![0, 1]);
+        let expected = matrix![DenseMatrix<f64> => (1.0, 0.0, -1.0, -1.0);(0.0, 1.0, 2.0, 1.0);(0.0, 0.0, 0.0, 0.0)];
+        assert_eq!(r, expected);
+        let sparse = matrix![SparseMatrix<f64> => (0.0, 2.0, 4.0, 2.0);(1.0, 1.0, 1.0, 0.0);(1.0, 2.0, 3.0, 1.0)];
+        let (r, pivots) = sparse.rref();
+        assert_eq!(pivots, vec![0, 1]);
+        for i in 0..3 {
+            for j in 0..4 {
+                assert_eq!(r.get_or_zero(i, j), *expected.get(i, j).unwrap());
+            }
+        }
+    }
+}
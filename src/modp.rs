@@ -0,0 +1,178 @@
+use std::ops::{Add, Sub, Mul, Div, Neg};
+use std::fmt::{Formatter, Display};
+
+use crate::matrix_base::*;
+
+/// An element of the prime field GF(P), stored as its residue in `0..P`.
+///
+/// `P` has to be prime for this to be a field; that is not checked up
+/// front, but inverting a non-unit panics.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ModP<const P : u64>(u64);
+
+impl<const P : u64> ModP<P> {
+    pub const fn new(v : u64) -> ModP<P> {
+        ModP(v % P)
+    }
+
+    /// Maps negative integers to their residue, e.g. `-1` to `P - 1`.
+    pub fn from_i64(v : i64) -> ModP<P> {
+        ModP((v as i128).rem_euclid(P as i128) as u64)
+    }
+
+    pub fn get_value(self : &Self) -> u64 {
+        self.0
+    }
+
+    /// Multiplicative inverse by the extended Euclidean algorithm.
+    pub fn inverse(self : &Self) -> ModP<P> {
+        let (mut r0, mut r1) = (P as i128, self.0 as i128);
+        let (mut t0, mut t1) = (0i128, 1i128);
+        while r1 != 0 {
+            let q = r0 / r1;
+            let r = r0 - q * r1;
+            r0 = r1;
+            r1 = r;
+            let t = t0 - q * t1;
+            t0 = t1;
+            t1 = t;
+        }
+        if r0 != 1 {
+            panic!("modp error : {} has no inverse modulo {}", self.0, P);
+        }
+        ModP(t0.rem_euclid(P as i128) as u64)
+    }
+}
+
+impl<const P : u64> Add for ModP<P> {
+    type Output = ModP<P>;
+    fn add(self : Self, other : Self) -> Self::Output {
+        ModP(((self.0 as u128 + other.0 as u128) % P as u128) as u64)
+    }
+}
+
+impl<const P : u64> Sub for ModP<P> {
+    type Output = ModP<P>;
+    fn sub(self : Self, other : Self) -> Self::Output {
+        self + (-other)
+    }
+}
+
+impl<const P : u64> Mul for ModP<P> {
+    type Output = ModP<P>;
+    fn mul(self : Self, other : Self) -> Self::Output {
+        ModP(((self.0 as u128 * other.0 as u128) % P as u128) as u64)
+    }
+}
+
+impl<const P : u64> Div for ModP<P> {
+    type Output = ModP<P>;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self : Self, other : Self) -> Self::Output {
+        self * other.inverse()
+    }
+}
+
+impl<const P : u64> Neg for ModP<P> {
+    type Output = ModP<P>;
+    fn neg(self : Self) -> Self::Output {
+        if self.0 == 0 { self } else { ModP(P - self.0) }
+    }
+}
+
+impl<const P : u64> AddMonoid for ModP<P> {
+    fn get_identity_add() -> ModP<P> {
+        ModP(0)
+    }
+}
+
+impl<const P : u64> Semiring for ModP<P> {
+    fn get_identity_mul() -> ModP<P> {
+        ModP::new(1)
+    }
+}
+
+impl<const P : u64> Ring for ModP<P> {}
+
+impl<const P : u64> Field for ModP<P> {
+    fn get_inverse_mul(self : &Self) -> ModP<P> {
+        self.inverse()
+    }
+}
+
+// Every non-zero element is an equally good pivot, so LU over GF(P) only
+// has to tell zero apart from the rest.
+impl<const P : u64> Magnitude for ModP<P> {
+    fn magnitude(self : &Self) -> f64 {
+        if self.0 == 0 { 0.0 } else { 1.0 }
+    }
+}
+
+impl<const P : u64> Display for ModP<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dense_matrix::DenseMatrix;
+    use crate::sparse_matrix::SparseMatrix;
+
+    type F7 = ModP<7>;
+
+    fn f(v : i64) -> F7 {
+        F7::from_i64(v)
+    }
+
+    #[test]
+    fn modp_arithmetic() {
+        assert_eq!(f(5) + f(4), f(2));
+        assert_eq!(f(2) - f(5), f(4));
+        assert_eq!(-f(3), f(4));
+        assert_eq!(f(-1).get_value(), 6);
+        assert_eq!(f(3) * f(5), f(1));
+        assert_eq!(f(3).inverse(), f(5));
+        for v in 1..7 {
+            assert_eq!(f(v) * f(v).get_inverse_mul(), f(1));
+        }
+        assert_eq!(f(2) / f(4), f(4));
+        // products of large residues go through u128
+        type Big = ModP<18446744073709551557>;
+        let x = Big::new(18446744073709551556);
+        assert_eq!(x * x, Big::new(1));
+        assert_eq!(x.inverse(), x);
+    }
+
+    #[test]
+    fn modp_matrices() {
+        // rows 0 and 1 sum to row 2 over GF(7)
+        let rows = [[1, 2, 3], [4, 5, 6], [5, 0, 2]];
+        let mut dense = DenseMatrix::<F7>::new(3, 3);
+        let mut sparse = SparseMatrix::<F7>::new(3, 3);
+        for i in 0..3 {
+            for j in 0..3 {
+                dense.set(i, j, f(rows[i][j]));
+                sparse.set(i, j, f(rows[i][j]));
+            }
+        }
+        assert_eq!(dense.rank(), 2);
+        assert_eq!(sparse.rank(), 2);
+        assert!(dense.inverse().is_none());
+        let kernel = dense.null_space();
+        assert_eq!((kernel.get_row(), kernel.get_column()), (3, 1));
+        let product = &dense * &kernel;
+        for i in 0..3 {
+            assert_eq!(*product.get(i, 0).unwrap(), f(0));
+        }
+        let sparse_kernel = sparse.null_space();
+        for i in 0..3 {
+            assert_eq!(sparse_kernel.get_or_zero(i, 0), *kernel.get(i, 0).unwrap());
+        }
+        dense.set(2, 2, f(0));
+        let inv = dense.inverse().unwrap();
+        assert_eq!(&dense * &inv, DenseMatrix::get_identity_matrix(3));
+        assert!(dense.null_space().get_column() == 0);
+    }
+}
@@ -0,0 +1,382 @@
+use crate::matrix_base::*;
+use crate::sparse_matrix::SparseMatrix;
+use crate::vector::Vector;
+use crate::error::*;
+
+/// The rows of a square sparse matrix as sorted `(col, value)` lists.
+fn sorted_rows(a : &SparseMatrix<f64>) -> Result<Vec<Vec<(usize, f64)>>, MatrixError> {
+    check_square((a.get_row(), a.get_column()))?;
+    Ok((0..a.get_row()).map(|i| a.get_iterator(i).map(|x| (x.get_col(), *x.get_v())).collect()).collect())
+}
+
+fn diagonal(rows : &[Vec<(usize, f64)>]) -> Result<Vec<f64>, MatrixError> {
+    rows.iter().enumerate().map(|(i, row)| {
+        match row.iter().find(|e| e.0 == i) {
+            Some(&(_, d)) if d != 0.0 => Ok(d),
+            _ => Err(MatrixError::Singular),
+        }
+    }).collect()
+}
+
+/// Dot product of two sorted sparse rows over the columns before `limit`.
+fn dot_before(x : &[(usize, f64)], y : &[(usize, f64)], limit : usize) -> f64 {
+    let (mut p, mut q) = (0, 0);
+    let mut s = 0.0;
+    while p < x.len() && q < y.len() && x[p].0 < limit && y[q].0 < limit {
+        if x[p].0 == y[q].0 {
+            s += x[p].1 * y[q].1;
+            p += 1;
+            q += 1;
+        } else if x[p].0 < y[q].0 {
+            p += 1;
+        } else {
+            q += 1;
+        }
+    }
+    s
+}
+
+fn to_sparse(rows : Vec<Vec<(usize, f64)>>) -> SparseMatrix<f64> {
+    let n = rows.len();
+    let mut m = SparseMatrix::new(n, n);
+    for (i, row) in rows.into_iter().enumerate() {
+        for (j, v) in row.into_iter() {
+            m.push_sorted(i, j, v);
+        }
+    }
+    m
+}
+
+/// An approximation `M` of a system matrix `A` that is cheap to invert.
+/// Iterative solvers call `apply` once per iteration to compute
+/// `z = M⁻¹ * r`; the closer `M⁻¹ * A` is to the identity, the fewer
+/// iterations they need.
+pub trait Preconditioner {
+    fn apply(self : &Self, r : &Vector<f64>) -> Vector<f64>;
+}
+
+/// `M = I`, i.e. no preconditioning.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IdentityPreconditioner;
+
+impl Preconditioner for IdentityPreconditioner {
+    fn apply(self : &Self, r : &Vector<f64>) -> Vector<f64> {
+        r.clone()
+    }
+}
+
+/// `M = diag(A)`. A zero on the diagonal is `Singular`.
+#[derive(Clone, Debug)]
+pub struct Jacobi {
+    inv_diag : Vec<f64>,
+}
+
+impl Jacobi {
+    pub fn new(a : &SparseMatrix<f64>) -> Jacobi {
+        Jacobi::try_new(a).unwrap_or_else(|e| panic!("jacobi preconditioner error : {}", e))
+    }
+
+    pub fn try_new(a : &SparseMatrix<f64>) -> Result<Jacobi, MatrixError> {
+        let diag = diagonal(&sorted_rows(a)?)?;
+        Ok(Jacobi {
+            inv_diag : diag.iter().map(|d| 1.0 / d).collect(),
+        })
+    }
+}
+
+impl Preconditioner for Jacobi {
+    fn apply(self : &Self, r : &Vector<f64>) -> Vector<f64> {
+        Vector::from_vec(r.as_slice().iter().zip(self.inv_diag.iter()).map(|(x, d)| x * d).collect())
+    }
+}
+
+/// Symmetric successive over-relaxation,
+/// `M = ω / (2 - ω) * (D / ω + L) * (D / ω)⁻¹ * (D / ω + U)` with `A = L + D + U`.
+/// Symmetric whenever `A` is, so it can be used with CG.
+#[derive(Clone, Debug)]
+pub struct SSOR {
+    rows : Vec<Vec<(usize, f64)>>,
+    diag : Vec<f64>,
+    omega : f64,
+}
+
+impl SSOR {
+    pub fn new(a : &SparseMatrix<f64>, omega : f64) -> SSOR {
+        SSOR::try_new(a, omega).unwrap_or_else(|e| panic!("ssor preconditioner error : {}", e))
+    }
+
+    /// `omega` must lie in `(0, 2)`, otherwise this fails with
+    /// `InvalidArgument`; `omega == 1` is symmetric Gauss-Seidel.
+    pub fn try_new(a : &SparseMatrix<f64>, omega : f64) -> Result<SSOR, MatrixError> {
+        if !(omega > 0.0 && omega < 2.0) {
+            return Err(MatrixError::InvalidArgument { reason : "ssor omega must lie in (0, 2)" });
+        }
+        let rows = sorted_rows(a)?;
+        let diag = diagonal(&rows)?;
+        Ok(SSOR {
+            rows : rows,
+            diag : diag,
+            omega : omega,
+        })
+    }
+}
+
+impl Preconditioner for SSOR {
+    fn apply(self : &Self, r : &Vector<f64>) -> Vector<f64> {
+        let n = self.diag.len();
+        let w = self.omega;
+        // (D / ω + L) * y = r
+        let mut y = r.as_slice().to_vec();
+        for i in 0..n {
+            let s : f64 = self.rows[i].iter().take_while(|e| e.0 < i).map(|&(j, v)| v * y[j]).sum();
+            y[i] = (y[i] - s) * w / self.diag[i];
+        }
+        // (D / ω + U) * z = (2 - ω) / ω * (D / ω) * y
+        for i in 0..n {
+            y[i] *= (2.0 - w) / w * self.diag[i] / w;
+        }
+        for i in (0..n).rev() {
+            let s : f64 = self.rows[i].iter().filter(|e| e.0 > i).map(|&(j, v)| v * y[j]).sum();
+            y[i] = (y[i] - s) * w / self.diag[i];
+        }
+        Vector::from_vec(y)
+    }
+}
+
+/// Incomplete LU with no fill-in: `L * U` matches `A` on the sparsity
+/// pattern of `A`, and both factors are kept in that same pattern (unit `L`
+/// strictly below the diagonal, `U` on and above it) in one `SparseMatrix`.
+#[derive(Clone)]
+pub struct ILU0 {
+    lu : SparseMatrix<f64>,
+}
+
+impl ILU0 {
+    pub fn new(a : &SparseMatrix<f64>) -> ILU0 {
+        ILU0::try_new(a).unwrap_or_else(|e| panic!("ilu0 preconditioner error : {}", e))
+    }
+
+    /// Fails with `Singular` on a missing or zero pivot.
+    pub fn try_new(a : &SparseMatrix<f64>) -> Result<ILU0, MatrixError> {
+        let mut rows = sorted_rows(a)?;
+        let n = rows.len();
+        // position[j] is the slot of column j in the current row, if any
+        let mut position = vec![usize::MAX; n];
+        let mut diag_slot = vec![usize::MAX; n];
+        for i in 0..n {
+            for (slot, e) in rows[i].iter().enumerate() {
+                position[e.0] = slot;
+            }
+            let mut slot = 0;
+            while slot < rows[i].len() && rows[i][slot].0 < i {
+                let k = rows[i][slot].0;
+                let pivot = rows[k][diag_slot[k]].1;
+                let factor = rows[i][slot].1 / pivot;
+                rows[i][slot].1 = factor;
+                let (upper, lower) = rows.split_at_mut(i);
+                for &(j, v) in upper[k][(diag_slot[k] + 1)..].iter() {
+                    if position[j] != usize::MAX {
+                        lower[0][position[j]].1 -= factor * v;
+                    }
+                }
+                slot += 1;
+            }
+            if slot == rows[i].len() || rows[i][slot].0 != i || rows[i][slot].1 == 0.0 {
+                return Err(MatrixError::Singular);
+            }
+            diag_slot[i] = slot;
+            for e in rows[i].iter() {
+                position[e.0] = usize::MAX;
+            }
+        }
+        Ok(ILU0 {
+            lu : to_sparse(rows),
+        })
+    }
+
+    /// Both factors in the pattern of `A`; the unit diagonal of `L` is not
+    /// stored.
+    pub fn get_factors(self : &Self) -> &SparseMatrix<f64> {
+        &self.lu
+    }
+}
+
+impl Preconditioner for ILU0 {
+    fn apply(self : &Self, r : &Vector<f64>) -> Vector<f64> {
+        let n = self.lu.get_row();
+        let mut y = r.as_slice().to_vec();
+        for i in 0..n {
+            let s : f64 = self.lu.get_iterator(i).take_while(|e| e.get_col() < i).map(|e| e.get_v() * y[e.get_col()]).sum();
+            y[i] -= s;
+        }
+        for i in (0..n).rev() {
+            let mut s = y[i];
+            let mut d = 0.0;
+            for e in self.lu.get_iterator(i).filter(|e| e.get_col() >= i) {
+                if e.get_col() == i {
+                    d = *e.get_v();
+                } else {
+                    s -= e.get_v() * y[e.get_col()];
+                }
+            }
+            y[i] = s / d;
+        }
+        Vector::from_vec(y)
+    }
+}
+
+/// Incomplete Cholesky with no fill-in, `M = L * Lᵀ` with `L` in the pattern
+/// of the lower triangle of `A`. Only the lower triangle of `A` is read.
+#[derive(Clone)]
+pub struct IC0 {
+    l : SparseMatrix<f64>,
+}
+
+impl IC0 {
+    pub fn new(a : &SparseMatrix<f64>) -> IC0 {
+        IC0::try_new(a).unwrap_or_else(|e| panic!("ic0 preconditioner error : {}", e))
+    }
+
+    /// Fails with `NotPositiveDefinite` when a pivot is not positive, which
+    /// can happen for some SPD matrices too since fill-in is dropped.
+    pub fn try_new(a : &SparseMatrix<f64>) -> Result<IC0, MatrixError> {
+        let rows = sorted_rows(a)?;
+        let n = rows.len();
+        let mut l : Vec<Vec<(usize, f64)>> = Vec::with_capacity(n);
+        for i in 0..n {
+            let mut row : Vec<(usize, f64)> = Vec::new();
+            let mut d = 0.0;
+            for &(j, v) in rows[i].iter().take_while(|e| e.0 <= i) {
+                if j < i {
+                    let s = v - dot_before(&row, &l[j], j);
+                    row.push((j, s / l[j].last().unwrap().1));
+                } else {
+                    d = v - row.iter().map(|e| e.1 * e.1).sum::<f64>();
+                }
+            }
+            if d <= 0.0 || d.is_nan() {
+                return Err(MatrixError::NotPositiveDefinite { pivot : i });
+            }
+            row.push((i, d.sqrt()));
+            l.push(row);
+        }
+        Ok(IC0 {
+            l : to_sparse(l),
+        })
+    }
+
+    /// The lower triangular factor.
+    pub fn get_l(self : &Self) -> &SparseMatrix<f64> {
+        &self.l
+    }
+}
+
+impl Preconditioner for IC0 {
+    fn apply(self : &Self, r : &Vector<f64>) -> Vector<f64> {
+        let n = self.l.get_row();
+        let mut y = r.as_slice().to_vec();
+        // L * y = r; the diagonal is the last entry of each row
+        let mut diag = vec![0.0; n];
+        for i in 0..n {
+            let mut s = y[i];
+            for e in self.l.get_iterator(i) {
+                if e.get_col() == i {
+                    diag[i] = *e.get_v();
+                } else {
+                    s -= e.get_v() * y[e.get_col()];
+                }
+            }
+            y[i] = s / diag[i];
+        }
+        // Lᵀ * z = y, scattering each solved entry into the earlier ones
+        for i in (0..n).rev() {
+            y[i] /= diag[i];
+            let zi = y[i];
+            for e in self.l.get_iterator(i).filter(|e| e.get_col() < i) {
+                y[e.get_col()] -= e.get_v() * zi;
+            }
+        }
+        Vector::from_vec(y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector;
+
+    /// tridiag(-1, 2, -1) plus `shift` on the diagonal and `skew` added
+    /// above it; ILU(0) and IC(0) are exact for tridiagonal matrices.
+    fn tridiagonal(n : usize, skew : f64) -> SparseMatrix<f64> {
+        let mut a = SparseMatrix::new(n, n);
+        for i in 0..n {
+            a.set(i, i, 2.0);
+            if i > 0 {
+                a.set(i, i - 1, -1.0);
+            }
+            if i + 1 < n {
+                a.set(i, i + 1, -1.0 + skew);
+            }
+        }
+        a
+    }
+
+    fn assert_inverts(a : &SparseMatrix<f64>, m : &dyn Preconditioner) {
+        let b = Vector::from_vec((0..a.get_row()).map(|i| (i * i % 7) as f64 - 3.0).collect());
+        let x = m.apply(&b);
+        let ax = a.mul_vector(&x);
+        for i in 0..b.length() {
+            assert!((ax[i] - b[i]).abs() < 1e-10, "{} != {}", ax[i], b[i]);
+        }
+    }
+
+    #[test]
+    fn exact_on_tridiagonal() {
+        let sym = tridiagonal(12, 0.0);
+        assert_inverts(&sym, &IC0::new(&sym));
+        assert_inverts(&sym, &ILU0::new(&sym));
+        let nonsym = tridiagonal(12, 0.5);
+        assert_inverts(&nonsym, &ILU0::new(&nonsym));
+        assert_eq!(ILU0::new(&nonsym).get_factors().nnz(), nonsym.nnz());
+        let mut diag = SparseMatrix::new(3, 3);
+        diag.set(0, 0, 2.0);
+        diag.set(1, 1, -4.0);
+        diag.set(2, 2, 0.5);
+        assert_inverts(&diag, &Jacobi::new(&diag));
+        assert_inverts(&diag, &SSOR::new(&diag, 1.0));
+    }
+
+    #[test]
+    fn ssor_matches_definition() {
+        // M = ω / (2 - ω) * (D / ω + L) * (D / ω)⁻¹ * (D / ω + U) for 2 x 2
+        let mut a = SparseMatrix::new(2, 2);
+        a.set(0, 0, 4.0);
+        a.set(0, 1, 1.0);
+        a.set(1, 0, 2.0);
+        a.set(1, 1, 3.0);
+        let w = 1.5;
+        let z = SSOR::new(&a, w).apply(&vector![1.0, 2.0]);
+        let (d0, d1) = (4.0 / w, 3.0 / w);
+        // multiply M * z out factor by factor, starting from the right
+        let u = [d0 * z[0] + z[1], d1 * z[1]];
+        let du = [u[0] / d0, u[1] / d1];
+        let m = [d0 * du[0], 2.0 * du[0] + d1 * du[1]];
+        let scale = w / (2.0 - w);
+        assert!((m[0] * scale - 1.0).abs() < 1e-12 && (m[1] * scale - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn preconditioner_errors() {
+        let mut a = SparseMatrix::new(2, 2);
+        a.set(0, 1, 1.0);
+        a.set(1, 0, 1.0);
+        assert_eq!(Jacobi::try_new(&a).unwrap_err(), MatrixError::Singular);
+        assert_eq!(ILU0::try_new(&a).err(), Some(MatrixError::Singular));
+        let diag = tridiagonal(3, 0.0);
+        assert_eq!(SSOR::try_new(&diag, 2.0).unwrap_err(), MatrixError::InvalidArgument { reason : "ssor omega must lie in (0, 2)" });
+        assert!(SSOR::try_new(&diag, f64::NAN).is_err());
+        let mut indefinite = tridiagonal(3, 0.0);
+        indefinite.set(2, 2, -1.0);
+        assert_eq!(IC0::try_new(&indefinite).err(), Some(MatrixError::NotPositiveDefinite { pivot : 2 }));
+    }
+}
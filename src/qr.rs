@@ -0,0 +1,310 @@
+use crate::matrix_base::*;
+use crate::dense_matrix::DenseMatrix;
+use crate::vector::Vector;
+use crate::error::*;
+
+/// Householder QR factorization of an `m x n` matrix: `A * P = Q * R`.
+///
+/// Storage is compact, as in LAPACK: `R` sits on and above the diagonal and
+/// the Householder vector of step `k` below it (its leading 1 is implicit),
+/// so `H_k = I - tau[k] * v_k * v_kᵀ` and `Q = H_0 * H_1 * ... * H_{p-1}`
+/// with `p = min(m, n)`. Without pivoting `P` is the identity.
+#[derive(Clone, Debug)]
+pub struct QR {
+    row : usize,
+    col : usize,
+    qr : Vec<f64>,
+    tau : Vec<f64>,
+    perm : Vec<usize>,
+    pivoted : bool,
+}
+
+impl QR {
+    pub fn new(m : &DenseMatrix<f64>) -> QR {
+        QR::factorize(m, false)
+    }
+
+    /// Rank-revealing QR: at every step the remaining column of largest
+    /// norm is moved to the front, so `|R[k][k]|` is non-increasing.
+    pub fn new_with_pivoting(m : &DenseMatrix<f64>) -> QR {
+        QR::factorize(m, true)
+    }
+
+    fn factorize(m : &DenseMatrix<f64>, pivoting : bool) -> QR {
+        let (row, col) = (m.get_row(), m.get_column());
+        let mut qr = QR {
+            row : row,
+            col : col,
+            qr : m.as_slice().to_vec(),
+            tau : Vec::with_capacity(row.min(col)),
+            perm : (0..col).collect(),
+            pivoted : pivoting,
+        };
+        for k in 0..row.min(col) {
+            if pivoting {
+                let mut p = k;
+                let mut max = -1.0;
+                for j in k..col {
+                    let norm : f64 = (k..row).map(|i| qr.at(i, j) * qr.at(i, j)).sum();
+                    if norm > max {
+                        max = norm;
+                        p = j;
+                    }
+                }
+                if p != k {
+                    for i in 0..row {
+                        qr.qr.swap(i * col + k, i * col + p);
+                    }
+                    qr.perm.swap(k, p);
+                }
+            }
+            let tau = qr.make_reflector(k);
+            qr.tau.push(tau);
+            for j in (k + 1)..col {
+                qr.reflect(k, |i| i * col + j);
+            }
+        }
+        qr
+    }
+
+    fn at(self : &Self, i : usize, j : usize) -> f64 {
+        self.qr[i * self.col + j]
+    }
+
+    /// Turns column k (rows k..m) into `beta * e_1` plus the stored
+    /// reflector below the diagonal, and returns its `tau`.
+    fn make_reflector(self : &mut Self, k : usize) -> f64 {
+        let col = self.col;
+        let alpha = self.at(k, k);
+        let tail : f64 = ((k + 1)..self.row).map(|i| self.at(i, k) * self.at(i, k)).sum();
+        if tail == 0.0 {
+            return 0.0;
+        }
+        let norm = (alpha * alpha + tail).sqrt();
+        let beta = if alpha >= 0.0 { -norm } else { norm };
+        let scale = 1.0 / (alpha - beta);
+        for i in (k + 1)..self.row {
+            self.qr[i * col + k] *= scale;
+        }
+        self.qr[k * col + k] = beta;
+        (beta - alpha) / beta
+    }
+
+    /// Applies `H_k` to the vector whose element `i` lives at `index(i)` in
+    /// the compact storage.
+    fn reflect<F : Fn(usize) -> usize>(self : &mut Self, k : usize, index : F) {
+        let tau = self.tau[k];
+        if tau == 0.0 {
+            return;
+        }
+        let mut s = self.qr[index(k)];
+        for i in (k + 1)..self.row {
+            s += self.at(i, k) * self.qr[index(i)];
+        }
+        s *= tau;
+        self.qr[index(k)] -= s;
+        for i in (k + 1)..self.row {
+            let v = self.at(i, k);
+            self.qr[index(i)] -= s * v;
+        }
+    }
+
+    /// Applies `H_k` to `x` in place.
+    fn reflect_vector(self : &Self, k : usize, x : &mut [f64]) {
+        let tau = self.tau[k];
+        if tau == 0.0 {
+            return;
+        }
+        let mut s = x[k];
+        for i in (k + 1)..self.row {
+            s += self.at(i, k) * x[i];
+        }
+        s *= tau;
+        x[k] -= s;
+        for i in (k + 1)..self.row {
+            x[i] -= s * self.at(i, k);
+        }
+    }
+
+    /// Column `j` of `A * P` is column `perm[j]` of `A`.
+    pub fn get_permutation(self : &Self) -> &[usize] {
+        &self.perm
+    }
+
+    /// The Householder scalars; the vectors are below the diagonal of
+    /// `get_compact`.
+    pub fn get_tau(self : &Self) -> &[f64] {
+        &self.tau
+    }
+
+    /// `R` and the reflectors packed into one `m x n` matrix.
+    pub fn get_compact(self : &Self) -> DenseMatrix<f64> {
+        DenseMatrix::from_vec(self.row, self.col, self.qr.clone())
+    }
+
+    /// The `min(m, n) x n` upper triangular factor.
+    pub fn get_r(self : &Self) -> DenseMatrix<f64> {
+        let p = self.row.min(self.col);
+        let mut r = DenseMatrix::new_with(p, self.col, 0.0);
+        for i in 0..p {
+            for j in i..self.col {
+                r.set(i, j, self.at(i, j));
+            }
+        }
+        r
+    }
+
+    fn build_q(self : &Self, q_col : usize) -> DenseMatrix<f64> {
+        let mut q = Vec::with_capacity(self.row * q_col);
+        let mut columns : Vec<Vec<f64>> = Vec::with_capacity(q_col);
+        for j in 0..q_col {
+            let mut x = vec![0.0; self.row];
+            x[j] = 1.0;
+            for k in (0..self.tau.len()).rev() {
+                self.reflect_vector(k, &mut x);
+            }
+            columns.push(x);
+        }
+        for i in 0..self.row {
+            for column in columns.iter() {
+                q.push(column[i]);
+            }
+        }
+        DenseMatrix::from_vec(self.row, q_col, q)
+    }
+
+    /// The first `min(m, n)` columns of `Q`.
+    pub fn get_q_thin(self : &Self) -> DenseMatrix<f64> {
+        self.build_q(self.row.min(self.col))
+    }
+
+    /// The full `m x m` orthogonal `Q`.
+    pub fn get_q_full(self : &Self) -> DenseMatrix<f64> {
+        self.build_q(self.row)
+    }
+
+    /// `Qᵀ * b`.
+    pub fn apply_qt(self : &Self, b : &Vector<f64>) -> Vector<f64> {
+        let mut x = b.as_slice().to_vec();
+        for k in 0..self.tau.len() {
+            self.reflect_vector(k, &mut x);
+        }
+        Vector::from_vec(x)
+    }
+
+    /// Number of diagonal entries of `R` larger than `tol * |R[0][0]|`.
+    /// Only meaningful for a pivoted factorization.
+    pub fn rank(self : &Self, tol : f64) -> usize {
+        let p = self.row.min(self.col);
+        if p == 0 {
+            return 0;
+        }
+        let threshold = tol * self.at(0, 0).abs();
+        (0..p).take_while(|&i| self.at(i, i).abs() > threshold && self.at(i, i) != 0.0).count()
+    }
+
+    /// Minimizes `|A * x - b|₂`.
+    ///
+    /// Without pivoting `A` must have full column rank (so `m >= n`). With
+    /// pivoting a rank-deficient `A` is allowed and the basic solution is
+    /// returned: the components beyond the numerical rank are zero.
+    pub fn solve_least_squares(self : &Self, b : &Vector<f64>) -> Option<Vector<f64>> {
+        match self.try_solve_least_squares(b) {
+            Ok(x) => Some(x),
+            Err(MatrixError::Singular) => None,
+            Err(e) => panic!("least squares error : {}", e),
+        }
+    }
+
+    pub fn try_solve_least_squares(self : &Self, b : &Vector<f64>) -> Result<Vector<f64>, MatrixError> {
+        if b.length() != self.row {
+            return Err(MatrixError::DimensionMismatch { left : (self.row, self.col), right : (b.length(), 1) });
+        }
+        let rank = if self.pivoted {
+            self.rank(f64::EPSILON * self.row.max(self.col) as f64)
+        } else {
+            if self.row < self.col || (0..self.col).any(|i| self.at(i, i) == 0.0) {
+                return Err(MatrixError::Singular);
+            }
+            self.col
+        };
+        let y = self.apply_qt(b);
+        let mut z = vec![0.0; rank];
+        for i in (0..rank).rev() {
+            let mut v = y[i];
+            for j in (i + 1)..rank {
+                v -= self.at(i, j) * z[j];
+            }
+            z[i] = v / self.at(i, i);
+        }
+        let mut x = Vector::new_with(self.col, 0.0);
+        for (j, v) in z.into_iter().enumerate() {
+            x.set(self.perm[j], v);
+        }
+        Ok(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{matrix, vector};
+
+    fn assert_close(a : &DenseMatrix<f64>, b : &DenseMatrix<f64>) {
+        assert_eq!(a.get_row(), b.get_row());
+        assert_eq!(a.get_column(), b.get_column());
+        for (x, y) in a.as_slice().iter().zip(b.as_slice().iter()) {
+            assert!((x - y).abs() < 1e-10, "{} != {}", x, y);
+        }
+    }
+
+    #[test]
+    fn qr_reconstructs() {
+        let a = matrix![DenseMatrix<f64> => (12.0, -51.0, 4.0);(6.0, 167.0, -68.0);(-4.0, 24.0, -41.0);(1.0, 2.0, 3.0)];
+        let qr = QR::new(&a);
+        let q = qr.get_q_thin();
+        assert_eq!(q.get_column(), 3);
+        assert_close(&(&q * &qr.get_r()), &a);
+        assert_close(&(&q.transpose() * &q), &DenseMatrix::get_identity_matrix(3));
+        let q_full = qr.get_q_full();
+        assert_close(&(&q_full.transpose() * &q_full), &DenseMatrix::get_identity_matrix(4));
+        let r = qr.get_r();
+        assert!(r.get(1, 0).unwrap().abs() < 1e-12);
+    }
+
+    #[test]
+    fn qr_least_squares() {
+        // fit y = 1 + 2x exactly through four points
+        let a = matrix![DenseMatrix<f64> => (1.0, 0.0);(1.0, 1.0);(1.0, 2.0);(1.0, 3.0)];
+        let b = vector![1.0, 3.0, 5.0, 7.0];
+        let x = QR::new(&a).solve_least_squares(&b).unwrap();
+        assert!((x[0] - 1.0).abs() < 1e-12);
+        assert!((x[1] - 2.0).abs() < 1e-12);
+        // overdetermined: mean of the observations
+        let ones = matrix![DenseMatrix<f64> => (1.0);(1.0);(1.0)];
+        let x = QR::new(&ones).solve_least_squares(&vector![1.0, 2.0, 6.0]).unwrap();
+        assert!((x[0] - 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn qr_pivoting_reveals_rank() {
+        let a = matrix![DenseMatrix<f64> => (1.0, 2.0, 3.0);(2.0, 4.0, 6.0);(1.0, 0.0, 1.0)];
+        let qr = QR::new_with_pivoting(&a);
+        assert_eq!(qr.rank(1e-10), 2);
+        assert_eq!(qr.get_permutation()[0], 2);
+        let mut ap = a.clone();
+        for i in 0..3 {
+            for j in 0..3 {
+                ap.set(i, j, *a.get(i, qr.get_permutation()[j]).unwrap());
+            }
+        }
+        assert_close(&(&qr.get_q_thin() * &qr.get_r()), &ap);
+        // b is in the column space, so the basic solution reproduces it
+        let b = vector![4.0, 8.0, 2.0];
+        let x = qr.solve_least_squares(&b).unwrap();
+        for i in 0..3 {
+            let ax : f64 = (0..3).map(|j| a.get(i, j).unwrap() * x[j]).sum();
+            assert!((ax - b[i]).abs() < 1e-10);
+        }
+    }
+}
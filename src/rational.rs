@@ -0,0 +1,210 @@
+use std::ops::{Add, Sub, Mul, Div, Neg};
+use std::cmp::Ordering;
+use std::fmt::{Formatter, Display};
+
+use crate::matrix_base::*;
+
+fn gcd(mut a : i128, mut b : i128) -> i128 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a.abs()
+}
+
+/// An exact fraction `num / den` with `den > 0` and `gcd(num, den) == 1`.
+///
+/// Arithmetic is carried out in `i128` and reduced before narrowing back, so
+/// intermediate products never overflow; a result that does not fit in
+/// `i64` after reduction panics, like integer overflow in debug builds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num : i64,
+    den : i64,
+}
+
+impl Rational {
+    pub fn new(num : i64, den : i64) -> Rational {
+        Rational::reduce(num as i128, den as i128)
+    }
+
+    pub fn from_integer(n : i64) -> Rational {
+        Rational {
+            num : n,
+            den : 1,
+        }
+    }
+
+    fn reduce(num : i128, den : i128) -> Rational {
+        if den == 0 {
+            panic!("rational error : zero denominator");
+        }
+        let g = gcd(num, den);
+        let (mut num, mut den) = (num / g, den / g);
+        if den < 0 {
+            num = -num;
+            den = -den;
+        }
+        if num < i64::MIN as i128 || num > i64::MAX as i128 || den > i64::MAX as i128 {
+            panic!("rational error : {}/{} overflows i64", num, den);
+        }
+        Rational {
+            num : num as i64,
+            den : den as i64,
+        }
+    }
+
+    pub fn get_numerator(self : &Self) -> i64 {
+        self.num
+    }
+
+    pub fn get_denominator(self : &Self) -> i64 {
+        self.den
+    }
+
+    pub fn to_f64(self : &Self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+}
+
+impl Default for Rational {
+    fn default() -> Rational {
+        Rational::from_integer(0)
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n : i64) -> Rational {
+        Rational::from_integer(n)
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+    fn add(self : Self, other : Self) -> Self::Output {
+        let (a, b, c, d) = (self.num as i128, self.den as i128, other.num as i128, other.den as i128);
+        Rational::reduce(a * d + c * b, b * d)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+    fn sub(self : Self, other : Self) -> Self::Output {
+        let (a, b, c, d) = (self.num as i128, self.den as i128, other.num as i128, other.den as i128);
+        Rational::reduce(a * d - c * b, b * d)
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+    fn mul(self : Self, other : Self) -> Self::Output {
+        Rational::reduce(self.num as i128 * other.num as i128, self.den as i128 * other.den as i128)
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+    fn div(self : Self, other : Self) -> Self::Output {
+        if other.num == 0 {
+            panic!("rational error : division by zero");
+        }
+        Rational::reduce(self.num as i128 * other.den as i128, self.den as i128 * other.num as i128)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+    fn neg(self : Self) -> Self::Output {
+        Rational::reduce(-(self.num as i128), self.den as i128)
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other : &Rational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other : &Rational) -> Ordering {
+        (self.num as i128 * other.den as i128).cmp(&(other.num as i128 * self.den as i128))
+    }
+}
+
+impl AddMonoid for Rational {
+    fn get_identity_add() -> Rational {
+        Rational::from_integer(0)
+    }
+}
+
+impl Semiring for Rational {
+    fn get_identity_mul() -> Rational {
+        Rational::from_integer(1)
+    }
+}
+
+impl Ring for Rational {}
+
+impl Field for Rational {}
+
+impl Magnitude for Rational {
+    fn magnitude(self : &Self) -> f64 {
+        self.to_f64().abs()
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dense_matrix::DenseMatrix;
+    use crate::matrix;
+
+    fn r(num : i64, den : i64) -> Rational {
+        Rational::new(num, den)
+    }
+
+    #[test]
+    fn rational_arithmetic() {
+        assert_eq!(r(2, -4), r(-1, 2));
+        assert_eq!(r(-1, 2).get_denominator(), 2);
+        assert_eq!(r(1, 2) + r(1, 3), r(5, 6));
+        assert_eq!(r(1, 2) - r(1, 3), r(1, 6));
+        assert_eq!(r(2, 3) * r(9, 4), r(3, 2));
+        assert_eq!(r(2, 3) / r(4, 9), r(3, 2));
+        assert_eq!(r(2, 3).get_inverse_mul(), r(3, 2));
+        assert!(r(1, 3) < r(1, 2));
+        assert_eq!(format!("{} {}", r(6, 3), r(-3, 9)), "2 -1/3");
+        // the intermediate product overflows i64 but the result does not
+        let big = r(i64::MAX, 3);
+        assert_eq!(big * r(3, i64::MAX), r(1, 1));
+    }
+
+    #[test]
+    fn rational_exact_inverse() {
+        // the Hilbert matrix is the classic ill-conditioned test case
+        let mut h = DenseMatrix::<Rational>::new(4, 4);
+        for i in 0..4 {
+            for j in 0..4 {
+                h.set(i, j, r(1, (i + j + 1) as i64));
+            }
+        }
+        let inv = h.inverse().unwrap();
+        assert_eq!(*inv.get(0, 0).unwrap(), r(16, 1));
+        assert_eq!(*inv.get(3, 3).unwrap(), r(2800, 1));
+        assert_eq!(&h * &inv, DenseMatrix::get_identity_matrix(4));
+        assert_eq!(h.determinant(), r(1, 6048000));
+        let singular = matrix![DenseMatrix<Rational> => (r(1, 2), r(1, 3));(r(3, 2), r(1, 1))];
+        assert!(singular.inverse().is_none());
+    }
+}
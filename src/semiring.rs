@@ -0,0 +1,128 @@
+use std::ops::{Add, Mul};
+use std::fmt::{Formatter, Display};
+
+use crate::matrix_base::*;
+
+/// The boolean semiring: `+` is logical or, `*` is logical and.
+/// Matrix products over it compute reachability.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Boolean(pub bool);
+
+impl Add for Boolean {
+    type Output = Boolean;
+    fn add(self : Self, other : Self) -> Self::Output {
+        Boolean(self.0 || other.0)
+    }
+}
+
+impl Mul for Boolean {
+    type Output = Boolean;
+    fn mul(self : Self, other : Self) -> Self::Output {
+        Boolean(self.0 && other.0)
+    }
+}
+
+impl AddMonoid for Boolean {
+    fn get_identity_add() -> Boolean {
+        Boolean(false)
+    }
+}
+
+impl Semiring for Boolean {
+    fn get_identity_mul() -> Boolean {
+        Boolean(true)
+    }
+}
+
+impl Display for Boolean {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The (min, +) tropical semiring: `+` takes the minimum and `*` adds.
+/// Matrix products over it compute shortest paths.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tropical<T>(pub T);
+
+impl<T : PartialOrd> Add for Tropical<T> {
+    type Output = Tropical<T>;
+    fn add(self : Self, other : Self) -> Self::Output {
+        if other.0 < self.0 {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl<T : Add<Output = T>> Mul for Tropical<T> {
+    type Output = Tropical<T>;
+    // Tropical multiplication is ordinary addition.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self : Self, other : Self) -> Self::Output {
+        Tropical(self.0 + other.0)
+    }
+}
+
+impl<T : Display> Display for Tropical<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// The additive identity is +inf. `Default` returns it as well, so freshly
+// created matrices start out filled with the semiring's zero.
+macro_rules! impl_semiring_for_tropical {
+    ($($t : ty),+) => {$(
+        impl Default for Tropical<$t> {
+            fn default() -> Tropical<$t> {
+                Tropical(<$t>::INFINITY)
+            }
+        }
+
+        impl AddMonoid for Tropical<$t> {
+            fn get_identity_add() -> Tropical<$t> {
+                Tropical(<$t>::INFINITY)
+            }
+        }
+
+        impl Semiring for Tropical<$t> {
+            fn get_identity_mul() -> Tropical<$t> {
+                Tropical(0.0)
+            }
+        }
+    )+};
+}
+
+impl_semiring_for_tropical!(f32, f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dense_matrix::DenseMatrix;
+    use crate::sparse_matrix::SparseMatrix;
+
+    #[test]
+    fn boolean_semiring() {
+        assert_eq!(Boolean(true) + Boolean(false), Boolean(true));
+        assert_eq!(Boolean(true) * Boolean(false), Boolean(false));
+        let m : DenseMatrix<Boolean> = Matrix::get_identity_matrix(2);
+        assert_eq!(*m.get(0, 0).unwrap(), Boolean(true));
+        assert_eq!(*m.get(0, 1).unwrap(), Boolean(false));
+    }
+
+    #[test]
+    fn tropical_semiring() {
+        let a = Tropical(3.0);
+        let b = Tropical(5.0);
+        assert_eq!(a + b, Tropical(3.0));
+        assert_eq!(a * b, Tropical(8.0));
+        assert_eq!(a + Tropical::get_identity_add(), a);
+        assert_eq!(a * Tropical::get_identity_mul(), a);
+        let mut m : SparseMatrix<Tropical<f64>> = MatrixInit::new(2, 2);
+        m.set(0, 1, Tropical(4.0));
+        m.add(0, 1, Tropical(2.0));
+        assert_eq!(*m.get(0, 1).unwrap(), Tropical(2.0));
+    }
+}
@@ -0,0 +1,664 @@
+use crate::matrix_base::*;
+use crate::sparse_matrix::SparseMatrix;
+use crate::csc_matrix::CscMatrix;
+use crate::vector::Vector;
+use crate::error::*;
+
+/// Pivots within this factor of the largest candidate in their column are
+/// accepted when they lie on the diagonal, in `SparseLU::new`.
+pub const DEFAULT_PIVOT_THRESHOLD : f64 = 0.1;
+
+const NONE : usize = usize::MAX;
+
+/// How `SymbolicLU` orders the columns before factorization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnOrdering {
+    /// Keep the columns as they are.
+    Natural,
+    /// Minimum degree on the pattern of `A + Aᵀ`, which keeps the fill low
+    /// as long as the pivots stay on the diagonal.
+    MinimumDegree,
+}
+
+/// The off-diagonal pattern of `A + Aᵀ`, one sorted list per vertex.
+fn symmetric_pattern(csc : &CscMatrix<f64>) -> Vec<Vec<usize>> {
+    let n = csc.get_column();
+    let mut adjacency : Vec<Vec<usize>> = vec![Vec::new(); n];
+    for j in 0..n {
+        for &i in csc.get_row_indices()[csc.get_col_ptr()[j]..csc.get_col_ptr()[j + 1]].iter() {
+            if i != j {
+                adjacency[i].push(j);
+                adjacency[j].push(i);
+            }
+        }
+    }
+    for list in adjacency.iter_mut() {
+        list.sort_unstable();
+        list.dedup();
+    }
+    adjacency
+}
+
+/// Vertices kept in doubly linked lists by degree, so the one of smallest
+/// degree is found without scanning them all.
+struct DegreeBuckets {
+    head : Vec<usize>,
+    next : Vec<usize>,
+    prev : Vec<usize>,
+    min : usize,
+}
+
+impl DegreeBuckets {
+    fn new(n : usize) -> DegreeBuckets {
+        DegreeBuckets {
+            head : vec![NONE; n.max(1)],
+            next : vec![NONE; n],
+            prev : vec![NONE; n],
+            min : 0,
+        }
+    }
+
+    fn insert(self : &mut Self, v : usize, degree : usize) {
+        self.prev[v] = NONE;
+        self.next[v] = self.head[degree];
+        if self.head[degree] != NONE {
+            self.prev[self.head[degree]] = v;
+        }
+        self.head[degree] = v;
+        self.min = self.min.min(degree);
+    }
+
+    fn remove(self : &mut Self, v : usize, degree : usize) {
+        if self.prev[v] == NONE {
+            self.head[degree] = self.next[v];
+        } else {
+            self.next[self.prev[v]] = self.next[v];
+        }
+        if self.next[v] != NONE {
+            self.prev[self.next[v]] = self.prev[v];
+        }
+    }
+
+    fn pop_min(self : &mut Self, degree : &[usize]) -> usize {
+        while self.head[self.min] == NONE {
+            self.min += 1;
+        }
+        let v = self.head[self.min];
+        self.remove(v, degree[v]);
+        v
+    }
+}
+
+/// Minimum degree ordering on a quotient graph, as in AMD: an eliminated
+/// vertex becomes an element that stands for the clique of its neighbours,
+/// so the fill is never stored explicitly and elements absorbed into a new
+/// one are freed. Degrees are the approximate external degrees of AMD,
+/// which bound the exact degree from above; only the neighbours of the
+/// pivot get new degrees at each step.
+fn minimum_degree(csc : &CscMatrix<f64>) -> Vec<usize> {
+    let n = csc.get_column();
+    // variables[i] and elements[i] are the variable and element neighbours
+    // of variable i; members[e] is the variable list of element e
+    let mut variables = symmetric_pattern(csc);
+    let mut elements : Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut members : Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut absorbed = vec![false; n];
+    let mut degree : Vec<usize> = variables.iter().map(|v| v.len()).collect();
+    let mut buckets = DegreeBuckets::new(n);
+    for v in 0..n {
+        buckets.insert(v, degree[v]);
+    }
+    let mut mark = vec![NONE; n];
+    let mut external = vec![0; n];
+    let mut external_mark = vec![NONE; n];
+    let mut order = Vec::with_capacity(n);
+    for k in 0..n {
+        let p = buckets.pop_min(&degree);
+        order.push(p);
+        // the new element: every variable reachable from p
+        mark[p] = k;
+        let mut lp = Vec::new();
+        for &i in variables[p].iter() {
+            if mark[i] != k {
+                mark[i] = k;
+                lp.push(i);
+            }
+        }
+        for &e in elements[p].iter() {
+            for &i in members[e].iter() {
+                if mark[i] != k {
+                    mark[i] = k;
+                    lp.push(i);
+                }
+            }
+            absorbed[e] = true;
+            members[e] = Vec::new();
+        }
+        variables[p] = Vec::new();
+        elements[p] = Vec::new();
+        for &i in lp.iter() {
+            buckets.remove(i, degree[i]);
+            // edges inside the new element are implied by it
+            variables[i].retain(|&j| mark[j] != k);
+            elements[i].retain(|&e| !absorbed[e]);
+        }
+        // external[e] = |members[e] \ lp| for the elements touching lp
+        for &i in lp.iter() {
+            for &e in elements[i].iter() {
+                if external_mark[e] != k {
+                    external_mark[e] = k;
+                    external[e] = members[e].len();
+                }
+                external[e] -= 1;
+            }
+        }
+        let remaining = n - k - 1;
+        for &i in lp.iter() {
+            let bound = variables[i].len() + lp.len() - 1 + elements[i].iter().map(|&e| external[e]).sum::<usize>();
+            degree[i] = bound.min(degree[i] + lp.len() - 1).min(remaining - 1);
+            elements[i].push(p);
+            buckets.insert(i, degree[i]);
+        }
+        members[p] = lp;
+    }
+    order
+}
+
+/// The pattern-only part of a sparse LU factorization, computed once and
+/// reused for every matrix with the same pattern: the fill-reducing column
+/// ordering, the elimination tree of the reordered `A + Aᵀ` and the column
+/// counts of its Cholesky factor. While every pivot stays on the diagonal
+/// the counts bound the sizes of `L` and `U` (exactly, for a structurally
+/// symmetric `A`) and `factorize` sizes their storage from them; row swaps
+/// change the pattern, so the reach of each column is still found during
+/// the numeric phase.
+#[derive(Clone, Debug)]
+pub struct SymbolicLU {
+    n : usize,
+    col_perm : Vec<usize>,
+    parent : Vec<usize>,
+    col_counts : Vec<usize>,
+    col_ptr : Vec<usize>,
+    row_indices : Vec<usize>,
+}
+
+impl SymbolicLU {
+    pub fn new(a : &SparseMatrix<f64>) -> SymbolicLU {
+        SymbolicLU::with_ordering(a, ColumnOrdering::MinimumDegree)
+    }
+
+    pub fn try_new(a : &SparseMatrix<f64>) -> Result<SymbolicLU, MatrixError> {
+        SymbolicLU::try_with_ordering(a, ColumnOrdering::MinimumDegree)
+    }
+
+    pub fn with_ordering(a : &SparseMatrix<f64>, ordering : ColumnOrdering) -> SymbolicLU {
+        SymbolicLU::try_with_ordering(a, ordering).unwrap_or_else(|e| panic!("sparse lu error : {}", e))
+    }
+
+    pub fn try_with_ordering(a : &SparseMatrix<f64>, ordering : ColumnOrdering) -> Result<SymbolicLU, MatrixError> {
+        check_square((a.get_row(), a.get_column()))?;
+        let n = a.get_row();
+        let csc = CscMatrix::from(a);
+        let col_perm : Vec<usize> = match ordering {
+            ColumnOrdering::Natural => (0..n).collect(),
+            ColumnOrdering::MinimumDegree => minimum_degree(&csc),
+        };
+        let mut position = vec![0; n];
+        for (k, &j) in col_perm.iter().enumerate() {
+            position[j] = k;
+        }
+        let pattern = symmetric_pattern(&csc);
+        // elimination tree by Liu's algorithm, with path compression
+        let mut parent = vec![NONE; n];
+        let mut ancestor = vec![NONE; n];
+        for k in 0..n {
+            for &j in pattern[col_perm[k]].iter() {
+                let mut i = position[j];
+                while i < k && ancestor[i] != NONE && ancestor[i] != k {
+                    let next = ancestor[i];
+                    ancestor[i] = k;
+                    i = next;
+                }
+                if i < k && ancestor[i] == NONE {
+                    ancestor[i] = k;
+                    parent[i] = k;
+                }
+            }
+        }
+        // row k of the factor is the union of the tree paths from its
+        // neighbours up to k
+        let mut col_counts = vec![1; n];
+        let mut mark = vec![NONE; n];
+        for k in 0..n {
+            mark[k] = k;
+            for &j in pattern[col_perm[k]].iter() {
+                let mut i = position[j];
+                while i < k && mark[i] != k {
+                    col_counts[i] += 1;
+                    mark[i] = k;
+                    i = parent[i];
+                }
+            }
+        }
+        Ok(SymbolicLU {
+            n : n,
+            col_perm : col_perm,
+            parent : parent,
+            col_counts : col_counts,
+            col_ptr : csc.get_col_ptr().to_vec(),
+            row_indices : csc.get_row_indices().to_vec(),
+        })
+    }
+
+    /// Column `k` of `A * Q` is column `perm[k]` of `A`.
+    pub fn get_column_permutation(self : &Self) -> &[usize] {
+        &self.col_perm
+    }
+
+    /// The parent of each step in the elimination tree, `None` for roots.
+    pub fn get_elimination_tree(self : &Self) -> Vec<Option<usize>> {
+        self.parent.iter().map(|&p| if p == NONE { None } else { Some(p) }).collect()
+    }
+
+    /// The factor sizes bounded by the column counts, assuming every pivot
+    /// stays on the diagonal.
+    pub fn get_predicted_statistics(self : &Self) -> FillStatistics {
+        let total : usize = self.col_counts.iter().sum();
+        FillStatistics {
+            nnz_a : self.row_indices.len(),
+            nnz_l : total - self.n,
+            nnz_u : total,
+        }
+    }
+
+    pub fn factorize(self : &Self, a : &SparseMatrix<f64>, threshold : f64) -> SparseLU {
+        self.try_factorize(a, threshold).unwrap_or_else(|e| panic!("sparse lu error : {}", e))
+    }
+
+    /// Numeric factorization `P * A * Q = L * U` by the left-looking
+    /// Gilbert-Peierls algorithm: column `k` of `L` and `U` comes from one
+    /// sparse triangular solve with the columns already computed, whose
+    /// nonzero pattern is found by a depth-first search first.
+    ///
+    /// Threshold partial pivoting: the diagonal entry is kept as pivot when
+    /// its magnitude is at least `threshold` times the largest candidate,
+    /// which preserves the ordering; otherwise the largest candidate wins.
+    /// `threshold == 1.0` is plain partial pivoting.
+    ///
+    /// Fails with `InvalidArgument` for a `threshold` outside `[0, 1]`, with
+    /// `InvalidStorage` if `a` does not have the analysed pattern and with
+    /// `Singular` when a column has no non-zero candidate.
+    pub fn try_factorize(self : &Self, a : &SparseMatrix<f64>, threshold : f64) -> Result<SparseLU, MatrixError> {
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(MatrixError::InvalidArgument { reason : "pivot threshold must lie in [0, 1]" });
+        }
+        if (a.get_row(), a.get_column()) != (self.n, self.n) {
+            return Err(MatrixError::DimensionMismatch { left : (self.n, self.n), right : (a.get_row(), a.get_column()) });
+        }
+        let csc = CscMatrix::from(a);
+        if csc.get_col_ptr() != &self.col_ptr[..] || csc.get_row_indices() != &self.row_indices[..] {
+            return Err(MatrixError::InvalidStorage { reason : "matrix pattern differs from the analysed one" });
+        }
+        let n = self.n;
+        // pinv[row] is the step at which the row was chosen as pivot
+        let mut pinv = vec![usize::MAX; n];
+        // L by columns with original row indices, unit diagonal implicit
+        let mut l : Vec<Vec<(usize, f64)>> = Vec::with_capacity(n);
+        // U by columns with step indices, diagonal kept apart
+        let mut u : Vec<Vec<(usize, f64)>> = Vec::with_capacity(n);
+        let mut u_diag : Vec<f64> = Vec::with_capacity(n);
+        let mut x = vec![0.0; n];
+        let mut visited = vec![usize::MAX; n];
+        let mut topo : Vec<usize> = Vec::with_capacity(self.col_counts.iter().copied().max().unwrap_or(0));
+        let mut stack : Vec<(usize, usize)> = Vec::new();
+        for k in 0..n {
+            let j = self.col_perm[k];
+            let (begin, end) = (csc.get_col_ptr()[j], csc.get_col_ptr()[j + 1]);
+            // reach of the column in the graph of L, in reverse topological order
+            topo.clear();
+            for &i in csc.get_row_indices()[begin..end].iter() {
+                if visited[i] == k {
+                    continue;
+                }
+                visited[i] = k;
+                stack.push((i, 0));
+                while let Some(&mut (node, ref mut next)) = stack.last_mut() {
+                    let children : &[(usize, f64)] = if pinv[node] == usize::MAX { &[] } else { &l[pinv[node]] };
+                    if let Some(&(child, _)) = children.get(*next) {
+                        *next += 1;
+                        if visited[child] != k {
+                            visited[child] = k;
+                            stack.push((child, 0));
+                        }
+                    } else {
+                        topo.push(node);
+                        stack.pop();
+                    }
+                }
+            }
+            for (&i, &v) in csc.get_row_indices()[begin..end].iter().zip(csc.get_values()[begin..end].iter()) {
+                x[i] = v;
+            }
+            for &i in topo.iter().rev() {
+                if pinv[i] != usize::MAX {
+                    let xi = x[i];
+                    for &(r, v) in l[pinv[i]].iter() {
+                        x[r] -= v * xi;
+                    }
+                }
+            }
+            // split the solved column into U (pivotal rows) and candidates
+            let mut u_col = Vec::with_capacity(self.col_counts[k]);
+            let mut best = usize::MAX;
+            let mut best_abs = 0.0;
+            for &i in topo.iter() {
+                if pinv[i] != usize::MAX {
+                    if x[i] != 0.0 {
+                        u_col.push((pinv[i], x[i]));
+                    }
+                } else if x[i].abs() > best_abs {
+                    best = i;
+                    best_abs = x[i].abs();
+                }
+            }
+            if best == usize::MAX {
+                return Err(MatrixError::Singular);
+            }
+            if visited[j] == k && pinv[j] == usize::MAX && x[j].abs() >= threshold * best_abs && x[j] != 0.0 {
+                best = j;
+            }
+            let pivot = x[best];
+            pinv[best] = k;
+            let mut l_col = Vec::with_capacity(self.col_counts[k] - 1);
+            for &i in topo.iter() {
+                if pinv[i] == usize::MAX && x[i] != 0.0 {
+                    l_col.push((i, x[i] / pivot));
+                }
+                x[i] = 0.0;
+            }
+            u_col.sort_unstable_by_key(|e| e.0);
+            l.push(l_col);
+            u.push(u_col);
+            u_diag.push(pivot);
+        }
+        // rows of L in step indices, now that every row has a step
+        for column in l.iter_mut() {
+            for e in column.iter_mut() {
+                e.0 = pinv[e.0];
+            }
+            column.sort_unstable_by_key(|e| e.0);
+        }
+        let mut row_perm = vec![0; n];
+        for (i, &k) in pinv.iter().enumerate() {
+            row_perm[k] = i;
+        }
+        let nnz_l : usize = l.iter().map(|c| c.len()).sum();
+        let nnz_u : usize = u.iter().map(|c| c.len()).sum::<usize>() + n;
+        Ok(SparseLU {
+            n : n,
+            row_perm : row_perm,
+            col_perm : self.col_perm.clone(),
+            l : l,
+            u : u,
+            u_diag : u_diag,
+            statistics : FillStatistics {
+                nnz_a : csc.nnz(),
+                nnz_l : nnz_l,
+                nnz_u : nnz_u,
+            },
+        })
+    }
+}
+
+/// Sizes of a sparse LU factorization. `nnz_l` leaves out the unit diagonal
+/// of `L`, `nnz_u` includes the diagonal of `U`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FillStatistics {
+    pub nnz_a : usize,
+    pub nnz_l : usize,
+    pub nnz_u : usize,
+}
+
+impl FillStatistics {
+    /// Entries of `L + U` that were not entries of `A`.
+    pub fn fill_in(self : &Self) -> usize {
+        (self.nnz_l + self.nnz_u).saturating_sub(self.nnz_a)
+    }
+
+    /// `nnz(L + U) / nnz(A)`, or 1 when `A` stores nothing (only the empty
+    /// matrix factorizes then, and it has nothing to fill).
+    pub fn fill_ratio(self : &Self) -> f64 {
+        if self.nnz_a == 0 {
+            return 1.0;
+        }
+        (self.nnz_l + self.nnz_u) as f64 / self.nnz_a as f64
+    }
+}
+
+/// A sparse LU factorization `P * A * Q = L * U`, see
+/// `SymbolicLU::try_factorize`.
+#[derive(Clone, Debug)]
+pub struct SparseLU {
+    n : usize,
+    row_perm : Vec<usize>,
+    col_perm : Vec<usize>,
+    l : Vec<Vec<(usize, f64)>>,
+    u : Vec<Vec<(usize, f64)>>,
+    u_diag : Vec<f64>,
+    statistics : FillStatistics,
+}
+
+impl SparseLU {
+    /// Minimum degree ordering and `DEFAULT_PIVOT_THRESHOLD`.
+    pub fn new(a : &SparseMatrix<f64>) -> SparseLU {
+        SparseLU::try_new(a).unwrap_or_else(|e| panic!("sparse lu error : {}", e))
+    }
+
+    pub fn try_new(a : &SparseMatrix<f64>) -> Result<SparseLU, MatrixError> {
+        SymbolicLU::try_new(a)?.try_factorize(a, DEFAULT_PIVOT_THRESHOLD)
+    }
+
+    /// Row `k` of `P * A` is row `perm[k]` of `A`.
+    pub fn get_row_permutation(self : &Self) -> &[usize] {
+        &self.row_perm
+    }
+
+    /// Column `k` of `A * Q` is column `perm[k]` of `A`.
+    pub fn get_column_permutation(self : &Self) -> &[usize] {
+        &self.col_perm
+    }
+
+    pub fn get_statistics(self : &Self) -> &FillStatistics {
+        &self.statistics
+    }
+
+    /// The unit lower triangular factor, with its diagonal stored.
+    pub fn get_l(self : &Self) -> SparseMatrix<f64> {
+        let mut m = SparseMatrix::new(self.n, self.n);
+        for (k, column) in self.l.iter().enumerate() {
+            m.set(k, k, 1.0);
+            for &(i, v) in column.iter() {
+                m.set(i, k, v);
+            }
+        }
+        m
+    }
+
+    pub fn get_u(self : &Self) -> SparseMatrix<f64> {
+        let mut m = SparseMatrix::new(self.n, self.n);
+        for (k, column) in self.u.iter().enumerate() {
+            for &(i, v) in column.iter() {
+                m.set(i, k, v);
+            }
+            m.set(k, k, self.u_diag[k]);
+        }
+        m
+    }
+
+    pub fn solve(self : &Self, b : &Vector<f64>) -> Vector<f64> {
+        self.try_solve(b).unwrap_or_else(|e| panic!("sparse lu solve error : {}", e))
+    }
+
+    pub fn try_solve(self : &Self, b : &Vector<f64>) -> Result<Vector<f64>, MatrixError> {
+        if b.length() != self.n {
+            return Err(MatrixError::DimensionMismatch { left : (self.n, self.n), right : (b.length(), 1) });
+        }
+        let mut y : Vec<f64> = self.row_perm.iter().map(|&i| b[i]).collect();
+        for (k, column) in self.l.iter().enumerate() {
+            let yk = y[k];
+            for &(i, v) in column.iter() {
+                y[i] -= v * yk;
+            }
+        }
+        for k in (0..self.n).rev() {
+            y[k] /= self.u_diag[k];
+            let yk = y[k];
+            for &(i, v) in self.u[k].iter() {
+                y[i] -= v * yk;
+            }
+        }
+        let mut x = Vector::new_with(self.n, 0.0);
+        for (k, &j) in self.col_perm.iter().enumerate() {
+            x.set(j, y[k]);
+        }
+        Ok(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::triplet::TripletBuilder;
+    use crate::vector;
+
+    fn assert_solves(a : &SparseMatrix<f64>, lu : &SparseLU) {
+        let n = a.get_row();
+        let b = Vector::from_vec((0..n).map(|i| (i % 4) as f64 - 1.5).collect());
+        let x = lu.solve(&b);
+        let ax = a.mul_vector(&x);
+        for i in 0..n {
+            assert!((ax[i] - b[i]).abs() < 1e-10, "{} != {}", ax[i], b[i]);
+        }
+    }
+
+    /// Diagonal plus a dense first row and column.
+    fn arrow(n : usize) -> SparseMatrix<f64> {
+        let mut t = TripletBuilder::new(n, n);
+        for i in 0..n {
+            t.push(i, i, 4.0 + i as f64);
+            if i > 0 {
+                t.push(0, i, 1.0);
+                t.push(i, 0, -1.0);
+            }
+        }
+        t.into_sparse()
+    }
+
+    #[test]
+    fn ordering_avoids_fill() {
+        let a = arrow(12);
+        let natural = SymbolicLU::with_ordering(&a, ColumnOrdering::Natural).factorize(&a, DEFAULT_PIVOT_THRESHOLD);
+        let ordered = SparseLU::new(&a);
+        assert_solves(&a, &natural);
+        assert_solves(&a, &ordered);
+        assert_eq!(ordered.get_statistics().fill_in(), 0);
+        assert_eq!(ordered.get_statistics().nnz_a, 34);
+        assert!(ordered.get_column_permutation()[..10].iter().all(|&j| j != 0));
+        // eliminating the hub first fills in everything
+        assert_eq!(natural.get_statistics().nnz_l + natural.get_statistics().nnz_u, 144);
+        assert!(natural.get_statistics().fill_ratio() > ordered.get_statistics().fill_ratio());
+        // diagonal pivots on a symmetric pattern fill exactly as predicted
+        let natural_symbolic = SymbolicLU::with_ordering(&a, ColumnOrdering::Natural);
+        assert_eq!(natural_symbolic.get_predicted_statistics(), *natural.get_statistics());
+        assert_eq!(SymbolicLU::new(&a).get_predicted_statistics(), *ordered.get_statistics());
+        assert_eq!(natural_symbolic.get_elimination_tree()[..3], [Some(1), Some(2), Some(3)]);
+        assert_eq!(natural_symbolic.get_elimination_tree()[11], None);
+        let empty = SparseLU::new(&SparseMatrix::new(0, 0));
+        assert_eq!(empty.get_statistics().fill_ratio(), 1.0);
+        assert_eq!(empty.solve(&Vector::new_with(0, 0.0)).length(), 0);
+    }
+
+    #[test]
+    fn large_grid() {
+        // a convection-diffusion stencil on a 60 x 60 grid, 3600 unknowns
+        let k = 60;
+        let mut t = TripletBuilder::new(k * k, k * k);
+        for i in 0..k {
+            for j in 0..k {
+                let row = i * k + j;
+                t.push(row, row, 4.0);
+                if i > 0 {
+                    t.push(row, row - k, -1.2);
+                }
+                if i + 1 < k {
+                    t.push(row, row + k, -0.8);
+                }
+                if j > 0 {
+                    t.push(row, row - 1, -1.1);
+                }
+                if j + 1 < k {
+                    t.push(row, row + 1, -0.9);
+                }
+            }
+        }
+        let a = t.into_sparse();
+        let symbolic = SymbolicLU::new(&a);
+        let lu = symbolic.factorize(&a, DEFAULT_PIVOT_THRESHOLD);
+        assert_solves(&a, &lu);
+        assert_eq!(symbolic.get_predicted_statistics(), *lu.get_statistics());
+        // the banded natural order keeps about k entries per column of each
+        // factor, far more than minimum degree
+        let banded = SymbolicLU::with_ordering(&a, ColumnOrdering::Natural).get_predicted_statistics();
+        assert!(2 * lu.get_statistics().nnz_l < banded.nnz_l, "{} vs {}", lu.get_statistics().nnz_l, banded.nnz_l);
+    }
+
+    #[test]
+    fn factors_reproduce_matrix() {
+        let mut t = TripletBuilder::new(6, 6);
+        let entries = [(0, 1, 2.0), (0, 4, 1.0), (1, 0, 3.0), (1, 2, -1.0), (2, 2, 0.5), (2, 5, 4.0),
+                       (3, 3, 1.0), (3, 0, 2.0), (4, 4, -2.0), (4, 1, 1.0), (5, 3, 1.5), (5, 5, 1.0)];
+        for &(i, j, v) in entries.iter() {
+            t.push(i, j, v);
+        }
+        let a = t.into_sparse();
+        let lu = SparseLU::new(&a);
+        assert_solves(&a, &lu);
+        let (p, q) = (lu.get_row_permutation(), lu.get_column_permutation());
+        let product = &lu.get_l() * &lu.get_u();
+        for i in 0..6 {
+            for j in 0..6 {
+                let expected = a.get(p[i], q[j]).cloned().unwrap_or(0.0);
+                let got = product.get(i, j).cloned().unwrap_or(0.0);
+                assert!((expected - got).abs() < 1e-12, "[{}, {}] {} != {}", i, j, expected, got);
+            }
+        }
+    }
+
+    #[test]
+    fn symbolic_reuse_and_errors() {
+        let a = arrow(8);
+        let symbolic = SymbolicLU::new(&a);
+        let mut b = a.clone();
+        for i in 0..8 {
+            b.set(i, i, 0.25);
+        }
+        // small diagonal entries force rows off the diagonal
+        let lu = symbolic.factorize(&b, 1.0);
+        assert_solves(&b, &lu);
+        assert!(lu.get_row_permutation().iter().zip(lu.get_column_permutation().iter()).any(|(r, c)| r != c));
+        let mut other = a.clone();
+        other.set(3, 5, 1.0);
+        assert_eq!(symbolic.try_factorize(&other, 0.1).unwrap_err(), MatrixError::InvalidStorage { reason : "matrix pattern differs from the analysed one" });
+        for threshold in [-0.5, 1.5, f64::NAN] {
+            assert_eq!(symbolic.try_factorize(&a, threshold).unwrap_err(), MatrixError::InvalidArgument { reason : "pivot threshold must lie in [0, 1]" });
+        }
+        let mut singular = SparseMatrix::new(2, 2);
+        singular.set(0, 0, 1.0);
+        singular.set(1, 0, 1.0);
+        assert_eq!(SparseLU::try_new(&singular).unwrap_err(), MatrixError::Singular);
+        let mut swap = SparseMatrix::new(2, 2);
+        swap.set(0, 1, 2.0);
+        swap.set(1, 0, 3.0);
+        assert_eq!(SparseLU::new(&swap).solve(&vector![4.0, 3.0]), vector![1.0, 2.0]);
+    }
+}
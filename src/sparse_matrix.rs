@@ -3,6 +3,9 @@ use std::ops::Mul;
 use std::fmt::{Formatter, Display};
 
 use crate::matrix_base::*;
+use crate::dense_matrix::DenseMatrix;
+use crate::vector::Vector;
+use crate::error::*;
 
 #[derive(Clone, Copy)]
 struct Item<T> {
@@ -50,15 +53,15 @@ impl<T : Default + Clone> TheRow<T> {
 pub struct IterItem<'a, T>(usize, &'a Item<T>);
 
 impl<'a, T> IterItem<'a, T> {
-    fn get_row(self : &Self) -> usize {
+    pub fn get_row(self : &Self) -> usize {
         self.0
     }
 
-    fn get_col(self : &Self) -> usize {
+    pub fn get_col(self : &Self) -> usize {
         self.1.index
     }
 
-    fn get_v(self : &Self) -> &T {
+    pub fn get_v(self : &Self) -> &T {
         &self.1.value
     }
 }
@@ -82,7 +85,7 @@ impl<'a, T> RowIterator<'a, T> {
 impl<'a, T> Iterator for RowIterator<'a, T> {
     type Item = IterItem<'a, T>;
     fn next(self : &mut Self) -> Option<Self::Item> {
-        if self.real_index as usize == self.holder.len() {
+        if self.real_index == self.holder.len() {
             None
         } else {
             let v = IterItem(self.row, &self.holder[self.real_index]);
@@ -106,7 +109,7 @@ impl<T : Default + Clone> MatrixInit<T> for SparseMatrix<T> {
             col : col,
             container : Default::default(),
         };
-        m.container.resize(row as usize, TheRow::new(0));
+        m.container.resize(row, TheRow::new(0));
         for row in 0..m.container.len() {
             m.container[row].0 = row;
         }
@@ -114,7 +117,20 @@ impl<T : Default + Clone> MatrixInit<T> for SparseMatrix<T> {
     }
 }
 
-impl<T : Display + Clone + Default> ConstMatrix<T> for SparseMatrix<T> {
+impl<T> SparseMatrix<T> {
+    /// Appends `value` at `[row, col]` without searching or sorting. The
+    /// caller must push each row's columns in increasing order.
+    pub(crate) fn push_sorted(self : &mut Self, row : usize, col : usize, value : T) {
+        self.container[row].1.push(Item::new(col, value));
+    }
+
+    /// Number of stored entries.
+    pub fn nnz(self : &Self) -> usize {
+        self.container.iter().map(|r| r.1.len()).sum()
+    }
+}
+
+impl<T : Clone + Default> ConstMatrix<T> for SparseMatrix<T> {
     fn get_row(self : &Self) -> usize {
         self.row
     }
@@ -166,30 +182,30 @@ impl<'a, T> MatrixIterator<'a, RowIterator<'a, T>> for SparseMatrix<T> {
     }
 }
 
-impl<T : Group<T> + Default + Clone + Copy + Add<Output = T> + Mul<Output = T> + Display + PartialEq> Matrix<T> for SparseMatrix<T> {
+impl<T : Semiring + Default + Copy + Display> Matrix<T> for SparseMatrix<T> {
     fn set(self : &mut Self, row : usize, col : usize, value : T) {
         let the_row  = &mut self.container[row].1;
         let the_item = the_row.iter_mut().find(|x| x.index == col);
-        if let Some(mut item) = the_item {
+        if let Some(item) = the_item {
             item.value = value;
             return;
         }
         the_row.push(Item::new(col, value));
         if the_row.len() > 1 {
-            the_row.sort_by(|a, b| a.index.cmp(&b.index));
+            the_row.sort_by_key(|a| a.index);
         } 
     }
 
     fn add(self : &mut Self, row : usize, col : usize, value : T) {
         let the_row = &mut self.container[row].1;
         let the_item = the_row.iter_mut().find(|x | x.index == col);
-        if let Some(mut item) = the_item {
+        if let Some(item) = the_item {
             item.value = item.value + value;
             return;
         }
         the_row.push(Item::new(col, value));
         if the_row.len() > 1 {
-            the_row.sort_by(|a, b| a.index.cmp(&b.index));
+            the_row.sort_by_key(|a| a.index);
         } 
     }
 
@@ -253,4 +269,209 @@ impl<T : Group<T> + Default + Clone + Copy + Add<Output = T> + Mul<Output = T> +
             self.container[row_i] = TheRow(row_i, tmp);   
         }
     }
-}
\ No newline at end of file
+}
+
+fn check_product_shape(a_row : usize, a_col : usize, b_row : usize, b_col : usize) -> Result<(), MatrixError> {
+    if a_col != b_row {
+        return Err(MatrixError::DimensionMismatch { left : (a_row, a_col), right : (b_row, b_col) });
+    }
+    Ok(())
+}
+
+impl<T : Semiring + Default + Copy + Display> SparseMatrix<T> {
+    /// Gustavson's algorithm: row i of the product is the merge of the rows
+    /// of `other` selected by the non-zeros of row i of `self`. A dense
+    /// accumulator plus a marker array keeps each merge O(flops).
+    pub fn try_mul_matrix(self : &Self, other : &SparseMatrix<T>) -> Result<SparseMatrix<T>, MatrixError> {
+        check_product_shape(self.row, self.col, other.row, other.col)?;
+        let mut m = SparseMatrix::new(self.row, other.col);
+        let mut acc : Vec<T> = vec![T::get_identity_add(); other.col];
+        let mut marker : Vec<usize> = vec![usize::MAX; other.col];
+        let mut cols : Vec<usize> = Vec::new();
+        for i in 0..self.row {
+            for a in self.container[i].1.iter() {
+                for b in other.container[a.index].1.iter() {
+                    if marker[b.index] != i {
+                        marker[b.index] = i;
+                        acc[b.index] = a.value * b.value;
+                        cols.push(b.index);
+                    } else {
+                        acc[b.index] = acc[b.index] + a.value * b.value;
+                    }
+                }
+            }
+            cols.sort_unstable();
+            m.container[i].1 = cols.iter().map(|&j| Item::new(j, acc[j])).collect();
+            cols.clear();
+        }
+        Ok(m)
+    }
+
+    /// `self * other` with a dense right-hand side.
+    pub fn try_mul_dense(self : &Self, other : &DenseMatrix<T>) -> Result<DenseMatrix<T>, MatrixError> {
+        check_product_shape(self.row, self.col, other.get_row(), other.get_column())?;
+        let mut m = DenseMatrix::new_with(self.row, other.get_column(), T::get_identity_add());
+        for i in 0..self.row {
+            for a in self.container[i].1.iter() {
+                for j in 0..other.get_column() {
+                    Matrix::add(&mut m, i, j, a.value * *other.get(a.index, j).unwrap());
+                }
+            }
+        }
+        Ok(m)
+    }
+
+    /// `other * self` with a dense left-hand side.
+    pub fn try_left_mul_dense(self : &Self, other : &DenseMatrix<T>) -> Result<DenseMatrix<T>, MatrixError> {
+        check_product_shape(other.get_row(), other.get_column(), self.row, self.col)?;
+        let mut m = DenseMatrix::new_with(other.get_row(), self.col, T::get_identity_add());
+        for i in 0..other.get_row() {
+            for k in 0..other.get_column() {
+                let a = *other.get(i, k).unwrap();
+                if a == T::get_identity_add() {
+                    continue;
+                }
+                for b in self.container[k].1.iter() {
+                    Matrix::add(&mut m, i, b.index, a * b.value);
+                }
+            }
+        }
+        Ok(m)
+    }
+}
+
+impl<T : Semiring + Copy> SparseMatrix<T> {
+    /// `self * v`, one pass over the stored entries.
+    pub fn try_mul_vector(self : &Self, v : &Vector<T>) -> Result<Vector<T>, MatrixError> {
+        if v.length() != self.col {
+            return Err(MatrixError::DimensionMismatch { left : (self.row, self.col), right : (v.length(), 1) });
+        }
+        let mut result = Vector::new_with(self.row, T::get_identity_add());
+        for (i, the_row) in self.container.iter().enumerate() {
+            let sum = the_row.1.iter().fold(T::get_identity_add(), |acc, a| acc + a.value * v[a.index]);
+            result.set(i, sum);
+        }
+        Ok(result)
+    }
+
+    pub fn mul_vector(self : &Self, v : &Vector<T>) -> Vector<T> {
+        self.try_mul_vector(v).unwrap_or_else(|e| panic!("matrix vector multiplication error : {}", e))
+    }
+}
+
+impl<'a, T : Semiring + Copy> Mul<&'a Vector<T>> for &'a SparseMatrix<T> {
+    type Output = Vector<T>;
+    fn mul(self : Self, v : &'a Vector<T>) -> Self::Output {
+        self.mul_vector(v)
+    }
+}
+
+fn unwrap_product<M>(result : Result<M, MatrixError>) -> M {
+    result.unwrap_or_else(|e| panic!("matrix multiplication error : {}", e))
+}
+
+impl<'a, T : Semiring + Default + Copy + Display> Mul<&'a SparseMatrix<T>> for &'a SparseMatrix<T> {
+    type Output = SparseMatrix<T>;
+    fn mul(self : Self, other : Self) -> Self::Output {
+        unwrap_product(self.try_mul_matrix(other))
+    }
+}
+
+impl<T : Semiring + Default + Copy + Display> Mul for SparseMatrix<T> {
+    type Output = SparseMatrix<T>;
+    fn mul(self : Self, other : Self) -> Self::Output {
+        unwrap_product(self.try_mul_matrix(&other))
+    }
+}
+
+impl<'a, T : Semiring + Default + Copy + Display> Mul<&'a DenseMatrix<T>> for &'a SparseMatrix<T> {
+    type Output = DenseMatrix<T>;
+    fn mul(self : Self, other : &'a DenseMatrix<T>) -> Self::Output {
+        unwrap_product(self.try_mul_dense(other))
+    }
+}
+
+impl<T : Semiring + Default + Copy + Display> Mul<DenseMatrix<T>> for SparseMatrix<T> {
+    type Output = DenseMatrix<T>;
+    fn mul(self : Self, other : DenseMatrix<T>) -> Self::Output {
+        unwrap_product(self.try_mul_dense(&other))
+    }
+}
+
+impl<'a, T : Semiring + Default + Copy + Display> Mul<&'a SparseMatrix<T>> for &'a DenseMatrix<T> {
+    type Output = DenseMatrix<T>;
+    fn mul(self : Self, other : &'a SparseMatrix<T>) -> Self::Output {
+        unwrap_product(other.try_left_mul_dense(self))
+    }
+}
+
+impl<T : Semiring + Default + Copy + Display> Mul<SparseMatrix<T>> for DenseMatrix<T> {
+    type Output = DenseMatrix<T>;
+    fn mul(self : Self, other : SparseMatrix<T>) -> Self::Output {
+        unwrap_product(other.try_left_mul_dense(&self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix;
+
+    fn sample() -> SparseMatrix<i32> {
+        // [[1, 0, 2],
+        //  [0, 0, 3],
+        //  [4, 5, 0]]
+        let mut m = SparseMatrix::new(3, 3);
+        m.set(0, 0, 1);
+        m.set(0, 2, 2);
+        m.set(1, 2, 3);
+        m.set(2, 1, 5);
+        m.set(2, 0, 4);
+        m
+    }
+
+    fn to_dense(m : &SparseMatrix<i32>) -> DenseMatrix<i32> {
+        let mut d = DenseMatrix::new(m.get_row(), m.get_column());
+        for i in 0..m.get_row() {
+            for each in m.get_iterator(i) {
+                d.set(i, each.get_col(), *each.get_v());
+            }
+        }
+        d
+    }
+
+    #[test]
+    fn sparse_times_sparse() {
+        let a = sample();
+        let c = &a * &a;
+        assert_eq!(to_dense(&c), &to_dense(&a) * &to_dense(&a));
+        let cols : Vec<usize> = c.get_iterator(2).map(|x| x.get_col()).collect();
+        assert_eq!(cols, vec![0, 2]);
+    }
+
+    #[test]
+    fn sparse_dense_mixed() {
+        let a = sample();
+        let b = matrix![DenseMatrix<i32> => (1, 2);(3, 4);(5, 6)];
+        assert_eq!(&a * &b, &to_dense(&a) * &b);
+        let c = matrix![DenseMatrix<i32> => (1, 0, 2);(0, 1, 1)];
+        assert_eq!(&c * &a, &c * &to_dense(&a));
+    }
+
+    #[test]
+    fn sparse_try_product() {
+        let a : SparseMatrix<i32> = SparseMatrix::new(2, 3);
+        let b : DenseMatrix<i32> = DenseMatrix::new(2, 2);
+        assert_eq!(a.try_mul_dense(&b).unwrap_err(), MatrixError::DimensionMismatch { left : (2, 3), right : (2, 2) });
+        assert_eq!(a.try_left_mul_dense(&b).unwrap().get_column(), 3);
+        assert_eq!(a.try_get(2, 0).unwrap_err(), MatrixError::IndexOutOfBounds { index : (2, 0), shape : (2, 3) });
+        assert_eq!(a.try_get(1, 1).unwrap(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "dimension mismatch")]
+    fn sparse_product_dimension_mismatch() {
+        let a : SparseMatrix<i32> = SparseMatrix::new(2, 3);
+        let _ = &a * &a;
+    }
+}
@@ -0,0 +1,276 @@
+use crate::matrix_base::*;
+use crate::dense_matrix::DenseMatrix;
+use crate::vector::Vector;
+use crate::error::*;
+
+const JACOBI_MAX_SWEEPS : usize = 64;
+
+fn dot(x : &[f64], y : &[f64]) -> f64 {
+    x.iter().zip(y.iter()).map(|(a, b)| a * b).sum()
+}
+
+/// Extends the orthonormal `basis` of vectors of length `dim` to `target`
+/// vectors with standard basis vectors, orthogonalized by Gram-Schmidt.
+fn complete_basis(basis : &mut Vec<Vec<f64>>, dim : usize, target : usize) {
+    for k in 0..dim {
+        if basis.len() == target {
+            return;
+        }
+        let mut x = vec![0.0; dim];
+        x[k] = 1.0;
+        // twice is enough for orthogonality to working precision
+        for _ in 0..2 {
+            for b in basis.iter() {
+                let d = dot(b, &x);
+                for (xi, bi) in x.iter_mut().zip(b.iter()) {
+                    *xi -= d * bi;
+                }
+            }
+        }
+        let norm = dot(&x, &x).sqrt();
+        if norm > 0.5 {
+            basis.push(x.iter().map(|v| v / norm).collect());
+        }
+    }
+}
+
+/// `rows x cols` matrix whose columns are the given vectors.
+fn from_columns(rows : usize, columns : &[Vec<f64>]) -> DenseMatrix<f64> {
+    let mut m = DenseMatrix::new_with(rows, columns.len(), 0.0);
+    for (j, c) in columns.iter().enumerate() {
+        for i in 0..rows {
+            m.set(i, j, c[i]);
+        }
+    }
+    m
+}
+
+/// Singular value decomposition `A = U * Σ * Vᵀ` of an `m x n` matrix, by
+/// one-sided (Hestenes) Jacobi rotations.
+///
+/// Singular values are in descending order. The thin form has
+/// `k = min(m, n)` columns in `U` and rows in `Vᵀ`; the full form has square
+/// `U` and `Vᵀ` and an `m x n` `Σ`.
+#[derive(Clone, Debug)]
+pub struct SVD {
+    row : usize,
+    col : usize,
+    u : DenseMatrix<f64>,
+    s : Vec<f64>,
+    vt : DenseMatrix<f64>,
+}
+
+impl SVD {
+    pub fn new(m : &DenseMatrix<f64>) -> SVD {
+        SVD::try_new(m).unwrap_or_else(|e| panic!("svd error : {}", e))
+    }
+
+    pub fn try_new(m : &DenseMatrix<f64>) -> Result<SVD, MatrixError> {
+        SVD::compute(m, false)
+    }
+
+    pub fn new_full(m : &DenseMatrix<f64>) -> SVD {
+        SVD::try_new_full(m).unwrap_or_else(|e| panic!("svd error : {}", e))
+    }
+
+    pub fn try_new_full(m : &DenseMatrix<f64>) -> Result<SVD, MatrixError> {
+        SVD::compute(m, true)
+    }
+
+    fn compute(m : &DenseMatrix<f64>, full : bool) -> Result<SVD, MatrixError> {
+        let (row, col) = (m.get_row(), m.get_column());
+        // orthogonalize the columns of A, or of Aᵀ when A is wide
+        let wide = row < col;
+        let (p, q) = if wide { (col, row) } else { (row, col) };
+        let mut a : Vec<Vec<f64>> = (0..q).map(|j| {
+            (0..p).map(|i| if wide { *m.get(j, i).unwrap() } else { *m.get(i, j).unwrap() }).collect()
+        }).collect();
+        let mut v : Vec<Vec<f64>> = (0..q).map(|j| {
+            let mut e = vec![0.0; q];
+            e[j] = 1.0;
+            e
+        }).collect();
+        let mut sweep = 0;
+        loop {
+            let mut rotated = false;
+            for i in 0..q {
+                for j in (i + 1)..q {
+                    let alpha = dot(&a[i], &a[i]);
+                    let beta = dot(&a[j], &a[j]);
+                    let gamma = dot(&a[i], &a[j]);
+                    if gamma == 0.0 || gamma.abs() <= f64::EPSILON * (alpha * beta).sqrt() {
+                        continue;
+                    }
+                    rotated = true;
+                    let zeta = (beta - alpha) / (2.0 * gamma);
+                    let t = zeta.signum() / (zeta.abs() + (1.0 + zeta * zeta).sqrt());
+                    let c = 1.0 / (1.0 + t * t).sqrt();
+                    let s = c * t;
+                    for w in [&mut a, &mut v].iter_mut() {
+                        let (left, right) = w.split_at_mut(j);
+                        for (x, y) in left[i].iter_mut().zip(right[0].iter_mut()) {
+                            let (xi, yi) = (*x, *y);
+                            *x = c * xi - s * yi;
+                            *y = s * xi + c * yi;
+                        }
+                    }
+                }
+            }
+            if !rotated {
+                break;
+            }
+            sweep += 1;
+            if sweep == JACOBI_MAX_SWEEPS {
+                return Err(MatrixError::NotConverged { iterations : sweep });
+            }
+        }
+        let norms : Vec<f64> = a.iter().map(|x| dot(x, x).sqrt()).collect();
+        let mut order : Vec<usize> = (0..q).collect();
+        order.sort_by(|&x, &y| norms[y].partial_cmp(&norms[x]).unwrap_or(std::cmp::Ordering::Equal));
+        let s : Vec<f64> = order.iter().map(|&k| norms[k]).collect();
+        // zero singular values sort last, so their left vectors are completed
+        let mut left : Vec<Vec<f64>> = order.iter().filter(|&&k| norms[k] != 0.0)
+            .map(|&k| a[k].iter().map(|x| x / norms[k]).collect()).collect();
+        let mut right : Vec<Vec<f64>> = order.iter().map(|&k| v[k].clone()).collect();
+        complete_basis(&mut left, p, if full { p } else { q });
+        if full {
+            complete_basis(&mut right, q, q);
+        }
+        let (u, vt) = if wide {
+            (from_columns(row, &right), from_columns(col, &left).transpose())
+        } else {
+            (from_columns(row, &left), from_columns(col, &right).transpose())
+        };
+        Ok(SVD {
+            row : row,
+            col : col,
+            u : u,
+            s : s,
+            vt : vt,
+        })
+    }
+
+    pub fn get_u(self : &Self) -> &DenseMatrix<f64> {
+        &self.u
+    }
+
+    pub fn get_vt(self : &Self) -> &DenseMatrix<f64> {
+        &self.vt
+    }
+
+    /// The `min(m, n)` singular values, largest first.
+    pub fn get_singular_values(self : &Self) -> Vector<f64> {
+        Vector::from_vec(self.s.clone())
+    }
+
+    /// `Σ` shaped to fit between `get_u` and `get_vt`.
+    pub fn get_sigma(self : &Self) -> DenseMatrix<f64> {
+        let mut sigma = DenseMatrix::new_with(self.u.get_column(), self.vt.get_row(), 0.0);
+        for (i, &v) in self.s.iter().enumerate() {
+            sigma.set(i, i, v);
+        }
+        sigma
+    }
+
+    /// Number of singular values larger than `tol * σ_max`.
+    pub fn rank(self : &Self, tol : f64) -> usize {
+        match self.s.first() {
+            Some(&max) => self.s.iter().filter(|&&v| v > tol * max && v != 0.0).count(),
+            None => 0,
+        }
+    }
+
+    /// Moore-Penrose pseudo-inverse `V * Σ⁺ * Uᵀ`, an `n x m` matrix.
+    /// Singular values up to `max(m, n) * ε * σ_max` are treated as zero.
+    pub fn pseudo_inverse(self : &Self) -> DenseMatrix<f64> {
+        let rank = self.rank(self.row.max(self.col) as f64 * f64::EPSILON);
+        let mut result = DenseMatrix::new_with(self.col, self.row, 0.0);
+        for i in 0..self.col {
+            for j in 0..self.row {
+                let mut v = 0.0;
+                for k in 0..rank {
+                    v += self.vt.get(k, i).unwrap() * self.u.get(j, k).unwrap() / self.s[k];
+                }
+                result.set(i, j, v);
+            }
+        }
+        result
+    }
+
+    /// The spectral norm `|A|₂ = σ_max`.
+    pub fn norm2(self : &Self) -> f64 {
+        self.s.first().cloned().unwrap_or(0.0)
+    }
+
+    /// `σ_max / σ_min`, infinite when `A` is rank deficient.
+    pub fn condition_number(self : &Self) -> f64 {
+        match self.s.last() {
+            Some(&min) if min != 0.0 => self.s[0] / min,
+            Some(_) => f64::INFINITY,
+            None => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix;
+
+    fn assert_close(a : &DenseMatrix<f64>, b : &DenseMatrix<f64>) {
+        assert_eq!(a.get_row(), b.get_row());
+        assert_eq!(a.get_column(), b.get_column());
+        for (x, y) in a.as_slice().iter().zip(b.as_slice().iter()) {
+            assert!((x - y).abs() < 1e-10, "{} != {}", x, y);
+        }
+    }
+
+    fn check(a : &DenseMatrix<f64>) {
+        let (m, n) = (a.get_row(), a.get_column());
+        let k = m.min(n);
+        let thin = SVD::new(a);
+        assert_eq!(thin.get_u().get_column(), k);
+        assert_eq!(thin.get_vt().get_row(), k);
+        assert_close(&(&(thin.get_u() * &thin.get_sigma()) * thin.get_vt()), a);
+        assert_close(&(&thin.get_u().transpose() * thin.get_u()), &DenseMatrix::get_identity_matrix(k));
+        let full = SVD::new_full(a);
+        assert_close(&(&(full.get_u() * &full.get_sigma()) * full.get_vt()), a);
+        assert_close(&(&full.get_u().transpose() * full.get_u()), &DenseMatrix::get_identity_matrix(m));
+        assert_close(&(full.get_vt() * &full.get_vt().transpose()), &DenseMatrix::get_identity_matrix(n));
+        let s = thin.get_singular_values();
+        for i in 1..k {
+            assert!(s[i - 1] >= s[i]);
+        }
+    }
+
+    #[test]
+    fn svd_shapes() {
+        let tall = matrix![DenseMatrix<f64> => (3.0, 2.0);(2.0, 3.0);(2.0, -2.0)];
+        check(&tall);
+        check(&tall.transpose());
+        let s = SVD::new(&tall).get_singular_values();
+        assert!((s[0] - 5.0).abs() < 1e-12 && (s[1] - 3.0).abs() < 1e-12);
+        // rank one, so U has to be completed
+        let outer = matrix![DenseMatrix<f64> => (1.0, 2.0, 3.0);(2.0, 4.0, 6.0);(3.0, 6.0, 9.0);(0.0, 0.0, 0.0)];
+        check(&outer);
+        check(&outer.transpose());
+    }
+
+    #[test]
+    fn svd_derived_quantities() {
+        let a = matrix![DenseMatrix<f64> => (2.0, 0.0);(0.0, 0.5)];
+        let svd = SVD::new(&a);
+        assert_eq!(svd.norm2(), 2.0);
+        assert_eq!(svd.condition_number(), 4.0);
+        assert_close(&svd.pseudo_inverse(), &matrix![DenseMatrix<f64> => (0.5, 0.0);(0.0, 2.0)]);
+        let singular = matrix![DenseMatrix<f64> => (1.0, 2.0);(2.0, 4.0);(0.0, 0.0)];
+        let svd = SVD::new(&singular);
+        assert_eq!(svd.rank(1e-12), 1);
+        assert_eq!(svd.condition_number(), f64::INFINITY);
+        let pinv = svd.pseudo_inverse();
+        // A * A⁺ * A = A and A⁺ * A * A⁺ = A⁺
+        assert_close(&(&(&singular * &pinv) * &singular), &singular);
+        assert_close(&(&(&pinv * &singular) * &pinv), &pinv);
+        assert_close(&pinv, &matrix![DenseMatrix<f64> => (0.04, 0.08, 0.0);(0.08, 0.16, 0.0)]);
+    }
+}
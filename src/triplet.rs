@@ -0,0 +1,130 @@
+use crate::matrix_base::*;
+use crate::sparse_matrix::SparseMatrix;
+use crate::csr_matrix::CsrMatrix;
+use crate::error::*;
+
+/// Coordinate (COO) assembly of a sparse matrix.
+///
+/// `push` only appends, in any order, and repeated positions are summed when
+/// the builder is finalized. This makes assembly O(nnz log nnz) overall
+/// instead of paying for a search and a sort on every `SparseMatrix::add`.
+#[derive(Clone, Debug)]
+pub struct TripletBuilder<T> {
+    row : usize,
+    col : usize,
+    entries : Vec<(usize, usize, T)>,
+}
+
+impl<T> TripletBuilder<T> {
+    pub fn new(row : usize, col : usize) -> TripletBuilder<T> {
+        TripletBuilder::with_capacity(row, col, 0)
+    }
+
+    pub fn with_capacity(row : usize, col : usize, capacity : usize) -> TripletBuilder<T> {
+        TripletBuilder {
+            row : row,
+            col : col,
+            entries : Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(self : &mut Self, row : usize, col : usize, value : T) {
+        if let Err(e) = self.try_push(row, col, value) {
+            panic!("triplet push error : {}", e);
+        }
+    }
+
+    pub fn try_push(self : &mut Self, row : usize, col : usize, value : T) -> Result<(), MatrixError> {
+        check_index(row, col, (self.row, self.col))?;
+        self.entries.push((row, col, value));
+        Ok(())
+    }
+
+    /// Number of pushed triplets, duplicates included.
+    pub fn len(self : &Self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(self : &Self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<T : AddMonoid> TripletBuilder<T> {
+    /// Sorts once by position and sums duplicates, giving CSR arrays.
+    fn compress(self : Self) -> (Vec<usize>, Vec<usize>, Vec<T>) {
+        let mut entries = self.entries;
+        entries.sort_by_key(|e| (e.0, e.1));
+        let mut row_ptr = vec![0; self.row + 1];
+        let mut col_indices : Vec<usize> = Vec::with_capacity(entries.len());
+        let mut values : Vec<T> = Vec::with_capacity(entries.len());
+        let mut last : Option<(usize, usize)> = None;
+        for (i, j, v) in entries.into_iter() {
+            if last == Some((i, j)) {
+                let sum = values.pop().unwrap() + v;
+                values.push(sum);
+            } else {
+                row_ptr[i + 1] += 1;
+                col_indices.push(j);
+                values.push(v);
+                last = Some((i, j));
+            }
+        }
+        for i in 0..self.row {
+            row_ptr[i + 1] += row_ptr[i];
+        }
+        (row_ptr, col_indices, values)
+    }
+
+    pub fn into_csr(self : Self) -> CsrMatrix<T> {
+        let (row, col) = (self.row, self.col);
+        let (row_ptr, col_indices, values) = self.compress();
+        CsrMatrix::new(row, col, row_ptr, col_indices, values)
+    }
+}
+
+impl<T : AddMonoid + Default> TripletBuilder<T> {
+    pub fn into_sparse(self : Self) -> SparseMatrix<T> {
+        let (row, col) = (self.row, self.col);
+        let (row_ptr, col_indices, values) = self.compress();
+        let mut m = SparseMatrix::new(row, col);
+        let mut values = values.into_iter();
+        for i in 0..row {
+            for &j in col_indices[row_ptr[i]..row_ptr[i + 1]].iter() {
+                m.push_sorted(i, j, values.next().unwrap());
+            }
+        }
+        m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triplet_sums_duplicates() {
+        let mut b = TripletBuilder::new(3, 3);
+        b.push(2, 1, 1.0);
+        b.push(0, 2, 2.0);
+        b.push(2, 1, 3.0);
+        b.push(0, 0, 5.0);
+        b.push(0, 2, -1.0);
+        assert_eq!(b.len(), 5);
+        let csr = b.clone().into_csr();
+        assert_eq!(csr.get_row_ptr(), &[0, 2, 2, 3]);
+        assert_eq!(csr.get_col_indices(), &[0, 2, 1]);
+        assert_eq!(csr.get_values(), &[5.0, 1.0, 4.0]);
+        let sparse = b.into_sparse();
+        assert_eq!(sparse.get(2, 1), Some(&4.0));
+        assert_eq!(sparse.get(0, 2), Some(&1.0));
+        assert_eq!(CsrMatrix::from(&sparse), csr);
+    }
+
+    #[test]
+    fn triplet_out_of_range() {
+        let mut b : TripletBuilder<f64> = TripletBuilder::new(2, 2);
+        assert_eq!(b.try_push(2, 0, 1.0).unwrap_err(), MatrixError::IndexOutOfBounds { index : (2, 0), shape : (2, 2) });
+        assert!(b.is_empty());
+    }
+}
@@ -19,6 +19,7 @@ impl<'a, T> Iterator for VectorIter<'a, T> {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct Vector<T> {
     container : Vec<T>,
 }
@@ -34,7 +35,6 @@ impl<T : Clone + Default> Vector<T> {
 }
 
 impl<T : Clone> Vector<T> {
-    #[warn(dead_code)]
     pub fn new_with(length : usize, value : T) -> Vector<T> {
         let mut v : Vector<T> = Vector {
             container : Vec::new(),
@@ -45,6 +45,20 @@ impl<T : Clone> Vector<T> {
 }
 
 impl<T> Vector<T> {
+    pub fn from_vec(container : Vec<T>) -> Vector<T> {
+        Vector {
+            container : container,
+        }
+    }
+
+    pub fn as_slice(self : &Self) -> &[T] {
+        &self.container
+    }
+
+    pub fn into_vec(self : Self) -> Vec<T> {
+        self.container
+    }
+
     pub fn set(self : &mut Self, index : usize, v : T) {
         self.container[index] = v;
     }
@@ -71,10 +85,8 @@ impl<T> Index<usize> for Vector<T> {
 impl<T : Display> std::fmt::Display for Vector<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "vector[{}] : \n", self.length()).unwrap();
-        let mut index : usize = 0;
-        for v in self.get_iterator() {
+        for (index, v) in self.get_iterator().enumerate() {
             write!(f, "[{}] = {} \n", index, v).unwrap();
-            index += 1;
         }
         write!(f, "")
     }
@@ -84,10 +96,9 @@ impl<T : Display> std::fmt::Display for Vector<T> {
 #[macro_export]
 macro_rules! vector {
     ($($var : expr),*) => {{ 
-        let mut v = Vec::new();
-        $(v.push($var);)*
+        let v = vec![$($var),*];
         let length = v.len();
-        let mut vv = Vector::new(length);
+        let mut vv = $crate::vector::Vector::new(length);
         let mut index = 0;
         for each in v.into_iter() {
             vv.set(index, each);
@@ -145,6 +156,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::explicit_counter_loop)]
     fn vector_iterator() {
         let mut v : Vector<i32> = Vector::new(3);
         v.set(0, 1);
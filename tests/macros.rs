@@ -0,0 +1,18 @@
+// The exported macros must expand correctly from outside the crate, without
+// the caller having to import the traits or helper macros they rely on.
+
+#[test]
+fn matrix_macro_from_another_crate() {
+    let m = matrix::matrix![matrix::DenseMatrix<f32> => (1.0, 2.0);(3.0, 4.0)];
+    use matrix::ConstMatrix;
+    assert_eq!(m.get_row(), 2);
+    assert_eq!(m.get_column(), 2);
+    assert_eq!(*m.get(1, 0).unwrap(), 3.0);
+}
+
+#[test]
+fn vector_macro_from_another_crate() {
+    let v : matrix::Vector<i32> = matrix::vector![1, 2, 3];
+    assert_eq!(v.length(), 3);
+    assert_eq!(v[2], 3);
+}